@@ -2,68 +2,17 @@
 
 extern crate test;
 
-mod polynomial;
-
-use crate::polynomial::{PolyRatio, Polynomial};
-
-use polynomial::Term;
-use polynomial::Variable;
+use sym_tfg::polynomial::{coeff, PolyRatio, Polynomial};
+use sym_tfg::{poly, ratio};
 use test::Bencher;
 
 #[bench]
 fn bench_polynomial_add(b: &mut Bencher) {
     let p1 = Polynomial {
-        terms: vec![
-            Term {
-                coefficient: 1.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "y".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 3.into(),
-                variables: vec![Variable {
-                    name: "z".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-        ],
-        degree: 3.into(),
-    };
-    let p2 = Polynomial {
-        terms: vec![
-            Term {
-                coefficient: 1.into(),
-                variables: vec![Variable {
-                    name: "a".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "b".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 3.into(),
-                variables: vec![Variable {
-                    name: "c".to_string(),
-                    degree: 4.into(),
-                }],
-            },
-        ],
-        degree: 1.into(),
+        pending_pow: 3.into(),
+        ..poly!(x + 2 y + 3 z)
     };
+    let p2 = poly!(a + 2 b + 3 c^4);
     b.iter(|| {
         let _ = p1.clone() + p2.clone();
     });
@@ -72,57 +21,10 @@ fn bench_polynomial_add(b: &mut Bencher) {
 #[bench]
 fn bench_polynomial_mul(b: &mut Bencher) {
     let p1 = Polynomial {
-        terms: vec![
-            Term {
-                coefficient: 1.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "y".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 3.into(),
-                variables: vec![Variable {
-                    name: "z".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-        ],
-        degree: 3.into(),
-    };
-    let p2 = Polynomial {
-        terms: vec![
-            Term {
-                coefficient: 1.into(),
-                variables: vec![Variable {
-                    name: "a".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "b".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 3.into(),
-                variables: vec![Variable {
-                    name: "c".to_string(),
-                    degree: 4.into(),
-                }],
-            },
-        ],
-        degree: 1.into(),
+        pending_pow: 3.into(),
+        ..poly!(x + 2 y + 3 z)
     };
+    let p2 = poly!(a + 2 b + 3 c^4);
     b.iter(|| {
         let _ = p1.clone() * p2.clone();
     });
@@ -130,35 +32,8 @@ fn bench_polynomial_mul(b: &mut Bencher) {
 
 #[bench]
 fn bench_polynomial_div(b: &mut Bencher) {
-    let p1 = Polynomial {
-        terms: vec![
-            Term {
-                coefficient: 8.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "y".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-        ],
-        degree: 1.into(),
-    };
-    let p2 = Polynomial {
-        terms: vec![Term {
-            coefficient: 2.into(),
-            variables: vec![Variable {
-                name: "x".to_string(),
-                degree: 1.into(),
-            }],
-        }],
-        degree: 1.into(),
-    };
+    let p1 = poly!(8 x + 2 y);
+    let p2 = poly!(2 x);
     b.iter(|| {
         let _ = p1.clone() / p2.clone();
     });
@@ -166,31 +41,7 @@ fn bench_polynomial_div(b: &mut Bencher) {
 
 #[bench]
 fn bench_polynomial_roots_linear(b: &mut Bencher) {
-    let p = Polynomial {
-        terms: vec![
-            Term {
-                coefficient: 3.into(),
-                variables: vec![
-                    Variable {
-                        name: "x".to_string(),
-                        degree: 1.into(),
-                    },
-                    Variable {
-                        name: "y".to_string(),
-                        degree: 1.into(),
-                    },
-                ],
-            },
-            Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "z".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-        ],
-        degree: 1.into(),
-    };
+    let p = poly!(3 x y + 2 z);
     b.iter(|| {
         let _ = p.clone().roots("x");
     });
@@ -198,38 +49,7 @@ fn bench_polynomial_roots_linear(b: &mut Bencher) {
 
 #[bench]
 fn bench_polynomial_roots_quadratic(b: &mut Bencher) {
-    let p = Polynomial {
-        terms: vec![
-            Term {
-                coefficient: 3.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 2.into(),
-                }],
-            },
-            Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 1.into(),
-                }],
-            },
-            Term {
-                coefficient: 1.into(),
-                variables: vec![
-                    Variable {
-                        name: "x".to_string(),
-                        degree: 0.into(),
-                    },
-                    Variable {
-                        name: "y".to_string(),
-                        degree: 1.into(),
-                    },
-                ],
-            },
-        ],
-        degree: 1.into(),
-    };
+    let p = poly!(3 x^2 + 2 x + x^0 y);
     b.iter(|| {
         let _ = p.clone().roots("x");
     });
@@ -239,112 +59,12 @@ fn bench_polynomial_roots_quadratic(b: &mut Bencher) {
 fn bench_polyratio_add(b: &mut Bencher) {
     let p1 = PolyRatio {
         numerator: Polynomial {
-            terms: vec![
-                Term {
-                    coefficient: 1.into(),
-                    variables: vec![Variable {
-                        name: "x".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 2.into(),
-                    variables: vec![Variable {
-                        name: "y".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 3.into(),
-                    variables: vec![Variable {
-                        name: "z".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-            ],
-            degree: 3.into(),
-        },
-        denominator: Polynomial {
-            terms: vec![
-                Term {
-                    coefficient: 1.into(),
-                    variables: vec![Variable {
-                        name: "a".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 2.into(),
-                    variables: vec![Variable {
-                        name: "b".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 3.into(),
-                    variables: vec![Variable {
-                        name: "c".to_string(),
-                        degree: 4.into(),
-                    }],
-                },
-            ],
-            degree: 1.into(),
-        },
-    };
-    let p2 = PolyRatio {
-        numerator: Polynomial {
-            terms: vec![
-                Term {
-                    coefficient: 1.into(),
-                    variables: vec![Variable {
-                        name: "x".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 2.into(),
-                    variables: vec![Variable {
-                        name: "y".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 3.into(),
-                    variables: vec![Variable {
-                        name: "z".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-            ],
-            degree: 3.into(),
-        },
-        denominator: Polynomial {
-            terms: vec![
-                Term {
-                    coefficient: 1.into(),
-                    variables: vec![Variable {
-                        name: "a".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 2.into(),
-                    variables: vec![Variable {
-                        name: "b".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 3.into(),
-                    variables: vec![Variable {
-                        name: "c".to_string(),
-                        degree: 4.into(),
-                    }],
-                },
-            ],
-            degree: 1.into(),
+            pending_pow: 3.into(),
+            ..poly!(x + 2 y + 3 z)
         },
+        denominator: poly!(a + 2 b + 3 c^4),
     };
+    let p2 = p1.clone();
     b.iter(|| {
         let _ = p1.clone() + p2.clone();
     });
@@ -352,68 +72,8 @@ fn bench_polyratio_add(b: &mut Bencher) {
 
 #[bench]
 fn bench_polyratio_div(b: &mut Bencher) {
-    let p1 = PolyRatio {
-        numerator: Polynomial {
-            terms: vec![
-                Term {
-                    coefficient: 1.into(),
-                    variables: vec![Variable {
-                        name: "x".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 2.into(),
-                    variables: vec![Variable {
-                        name: "y".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-            ],
-            degree: 1.into(),
-        },
-        denominator: Polynomial {
-            terms: vec![Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 1.into(),
-                }],
-            }],
-            degree: 1.into(),
-        },
-    };
-    let p2 = PolyRatio {
-        numerator: Polynomial {
-            terms: vec![
-                Term {
-                    coefficient: 1.into(),
-                    variables: vec![Variable {
-                        name: "x".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 2.into(),
-                    variables: vec![Variable {
-                        name: "y".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-            ],
-            degree: 1.into(),
-        },
-        denominator: Polynomial {
-            terms: vec![Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 1.into(),
-                }],
-            }],
-            degree: 1.into(),
-        },
-    };
+    let p1 = ratio!(x + 2 y; 2 x);
+    let p2 = ratio!(x + 2 y; 2 x);
     b.iter(|| {
         let _ = p1.clone() / p2.clone();
     });
@@ -421,38 +81,92 @@ fn bench_polyratio_div(b: &mut Bencher) {
 
 #[bench]
 fn bench_polyratio_simplify(b: &mut Bencher) {
-    let p = PolyRatio {
-        numerator: Polynomial {
-            terms: vec![
-                Term {
-                    coefficient: 1.into(),
-                    variables: vec![Variable {
-                        name: "x".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-                Term {
-                    coefficient: 2.into(),
-                    variables: vec![Variable {
-                        name: "y".to_string(),
-                        degree: 1.into(),
-                    }],
-                },
-            ],
-            degree: 1.into(),
-        },
-        denominator: Polynomial {
-            terms: vec![Term {
-                coefficient: 2.into(),
-                variables: vec![Variable {
-                    name: "x".to_string(),
-                    degree: 1.into(),
-                }],
-            }],
-            degree: 1.into(),
-        },
-    };
+    let p = ratio!(x + 2 y; 2 x);
     b.iter(|| {
         let _ = p.clone().simplify();
     });
 }
+
+/// x^3*y^2 + 2x^2*y - 5x + 7, for comparing the ways to evaluate a polynomial.
+fn evaluation_polynomial() -> Polynomial {
+    poly!(x^3 y^2 + 2 x^2 y - 5 x y^0 + 7 x^0 y^0)
+}
+
+#[bench]
+fn bench_polynomial_evaluate(b: &mut Bencher) {
+    let p = evaluation_polynomial();
+    let values = vec![
+        ("x".to_string(), coeff(3) / coeff(2)),
+        ("y".to_string(), coeff(-2)),
+    ];
+    b.iter(|| {
+        let mut q = p.clone();
+        let _ = q.evaluate(&values);
+    });
+}
+
+#[bench]
+fn bench_polynomial_eval_at(b: &mut Bencher) {
+    let p = evaluation_polynomial();
+    let values = vec![
+        ("x".to_string(), coeff(3) / coeff(2)),
+        ("y".to_string(), coeff(-2)),
+    ];
+    b.iter(|| {
+        let _ = p.eval_at(&values);
+    });
+}
+
+#[bench]
+fn bench_polynomial_evaluate_f64(b: &mut Bencher) {
+    let p = evaluation_polynomial();
+    let values = [("x", 1.5), ("y", -2.0)];
+    b.iter(|| {
+        let _ = p.evaluate_f64(&values);
+    });
+}
+
+#[bench]
+fn bench_polynomial_pow(b: &mut Bencher) {
+    let p = Polynomial::var("a") + Polynomial::var("b");
+    b.iter(|| {
+        let _ = p.powi(10);
+    });
+}
+
+#[bench]
+fn bench_polynomial_repeated_mul(b: &mut Bencher) {
+    let p = Polynomial::var("a") + Polynomial::var("b");
+    b.iter(|| {
+        let mut q = p.clone();
+        for _ in 1..10 {
+            q = q * p.clone();
+        }
+    });
+}
+
+#[bench]
+fn bench_polynomial_binomial_powi(b: &mut Bencher) {
+    let p = Polynomial::var("x") + Polynomial::constant(coeff(1));
+    b.iter(|| {
+        let _ = p.powi(20);
+    });
+}
+
+#[bench]
+fn bench_polynomial_binomial_expand_pow(b: &mut Bencher) {
+    let p = Polynomial::var("x") + Polynomial::constant(coeff(1));
+    b.iter(|| {
+        let _ = p.expand_pow(20);
+    });
+}
+
+/// (3x+2)^12 has coefficients up to about 10^8, which still fit in 64 bits. Run it with and without
+/// `--features bigrational` to see what arbitrary precision costs.
+#[bench]
+fn bench_polynomial_coefficient_growth(b: &mut Bencher) {
+    let p = Polynomial::monomial(coeff(3), &[("x", 1.into())]) + Polynomial::constant(coeff(2));
+    b.iter(|| {
+        let _ = p.powi(12);
+    });
+}
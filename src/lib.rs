@@ -0,0 +1,3 @@
+mod macros;
+pub mod parse;
+pub mod polynomial;
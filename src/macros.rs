@@ -0,0 +1,149 @@
+//! Macros to write polynomials in code the way they are written in the input, instead of as struct literals.
+
+/// Builds a `Polynomial` from terms written like `poly!(3 x^2 y - 1/2 x + 4)`. Each term is an optional integer or
+/// fraction followed by variables, which can have integer exponents like `x^2` and `x^-1` or fractional ones like
+/// `x^(1/2)`. Factors are separated by spaces or `*`, since a number cannot be followed directly by a letter in Rust.
+///
+/// The terms are kept as written, like in a struct literal: call `simplify` to add like terms.
+///
+/// ```
+/// use sym_tfg::poly;
+///
+/// let p = poly!(3 x^2 y - 1/2 x + 4);
+/// assert_eq!(p.as_string(), "3x^(2)y-1/2x+4");
+/// ```
+///
+/// ```compile_fail
+/// use sym_tfg::poly;
+///
+/// let p = poly!(3 x^y);
+/// ```
+#[macro_export]
+macro_rules! poly {
+    () => {
+        $crate::polynomial::Polynomial {
+            terms: Vec::<$crate::polynomial::Term>::new(),
+            pending_pow: 1.into(),
+        }
+    };
+    ($($tokens:tt)+) => {{
+        let mut terms: Vec<$crate::polynomial::Term> = Vec::new();
+        $crate::__poly_terms!(terms; $($tokens)+);
+        $crate::polynomial::Polynomial {
+            terms,
+            pending_pow: 1.into(),
+        }
+    }};
+}
+
+/// Splits the tokens of `poly!` into terms at each `+` or `-`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __poly_terms {
+    ($terms:ident; - $($rest:tt)+) => {
+        $crate::__poly_factors!($terms; $crate::polynomial::coeff(-1); []; $($rest)+);
+    };
+    ($terms:ident; + $($rest:tt)+) => {
+        $crate::__poly_factors!($terms; $crate::polynomial::coeff(1); []; $($rest)+);
+    };
+    ($terms:ident; $($rest:tt)+) => {
+        $crate::__poly_factors!($terms; $crate::polynomial::coeff(1); []; $($rest)+);
+    };
+}
+
+/// Collects the coefficient and the variables of one term of `poly!`, then pushes it and goes on with the next one.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __poly_factors {
+    ($terms:ident; $c:expr; [$($vars:expr),*];) => {
+        $terms.push($crate::polynomial::Term {
+            coefficient: $c,
+            variables: vec![$($vars),*],
+        });
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; + $($rest:tt)+) => {
+        $crate::__poly_factors!($terms; $c; [$($vars),*];);
+        $crate::__poly_terms!($terms; + $($rest)+);
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; - $($rest:tt)+) => {
+        $crate::__poly_factors!($terms; $c; [$($vars),*];);
+        $crate::__poly_terms!($terms; - $($rest)+);
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; * $($rest:tt)*) => {
+        $crate::__poly_factors!($terms; $c; [$($vars),*]; $($rest)*);
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; $n:literal / $d:literal $($rest:tt)*) => {
+        $crate::__poly_factors!(
+            $terms;
+            $c * $crate::polynomial::coeff($n) / $crate::polynomial::coeff($d);
+            [$($vars),*];
+            $($rest)*
+        );
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; $n:literal $($rest:tt)*) => {
+        $crate::__poly_factors!($terms; $c * $crate::polynomial::coeff($n); [$($vars),*]; $($rest)*);
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; $v:ident ^ ($n:literal / $d:literal) $($rest:tt)*) => {
+        $crate::__poly_factors!(
+            $terms;
+            $c;
+            [$($vars,)* $crate::__poly_variable!($v, $n, $d)];
+            $($rest)*
+        );
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; $v:ident ^ $n:literal $($rest:tt)*) => {
+        $crate::__poly_factors!(
+            $terms;
+            $c;
+            [$($vars,)* $crate::__poly_variable!($v, $n, 1)];
+            $($rest)*
+        );
+    };
+    ($terms:ident; $c:expr; [$($vars:expr),*]; $v:ident $($rest:tt)*) => {
+        $crate::__poly_factors!(
+            $terms;
+            $c;
+            [$($vars,)* $crate::__poly_variable!($v, 1, 1)];
+            $($rest)*
+        );
+    };
+}
+
+/// Builds the variable `v` to the power of `n/d` for `poly!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __poly_variable {
+    ($v:ident, $n:literal, $d:literal) => {{
+        let mut variable = $crate::polynomial::Variable {
+            name: stringify!($v).to_string(),
+            degree: From::<i64>::from($n),
+        };
+        variable.degree /= $d;
+        variable
+    }};
+}
+
+/// Builds a `PolyRatio` from a numerator and a denominator written like in `poly!`, separated by a semicolon:
+/// `ratio!(x + 1; x - 1)`.
+///
+/// ```
+/// use sym_tfg::ratio;
+///
+/// let r = ratio!(x + 1; x - 1);
+/// assert_eq!(r.as_string().unwrap(), "(x+1) / (x-1)");
+/// ```
+#[macro_export]
+macro_rules! ratio {
+    (@numerator [$($numerator:tt)*] ; $($denominator:tt)+) => {
+        $crate::polynomial::PolyRatio {
+            numerator: $crate::poly!($($numerator)*),
+            denominator: $crate::poly!($($denominator)+),
+        }
+    };
+    (@numerator [$($numerator:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::ratio!(@numerator [$($numerator)* $next] $($rest)*)
+    };
+    ($($tokens:tt)+) => {
+        $crate::ratio!(@numerator [] $($tokens)+)
+    };
+}
@@ -1,270 +1,1050 @@
 use num::rational::Rational64;
-use pest::iterators::Pairs;
+use num::{One, Zero};
+use pest::error::InputLocation;
+use pest::iterators::{Pair, Pairs};
 use pest::Parser;
-use pest_derive::Parser;
-use polynomial::PolyRatio;
+use std::collections::BTreeSet;
 use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use sym_tfg::parse::{
+    as_polynomial, divisors, parse_number, parse_operation, parse_polynomial, ParseError,
+    PolyParser, Rule, Scope,
+};
+use sym_tfg::polynomial;
+use sym_tfg::polynomial::{Coeff, LimitResult, PolyError, PolyRatio};
 
-mod polynomial;
-
-#[derive(Parser)]
-#[grammar = "poly.pest"]
-pub struct PolyParser;
+/// Returns the value of a polynomial without variables, or `None` if it has any.
+fn constant_value(p: &polynomial::Polynomial) -> Option<Coeff> {
+    if p.terms.iter().all(|t| t.variables.is_empty()) {
+        Some(p.terms.iter().map(|t| &t.coefficient).sum())
+    } else {
+        None
+    }
+}
 
-fn variable_from_string(var: &str) -> polynomial::Variable {
-    let mut iter = var.split('^');
-    let name = iter.next().unwrap().to_string();
-    let degree = iter
-        .next()
-        .map(|d| {
-            let clean = d.replace("(", "").replace(")", "");
-            if d.contains('/') {
-                let parts: Vec<&str> = clean.split('/').collect();
-                let numerator = parts[0].trim().parse::<i64>().unwrap_or(1);
-                let denominator = parts[1].trim().parse::<i64>().unwrap_or(1);
-                Rational64::new(numerator, denominator)
-            } else if d.contains('.') {
-                let parts: Vec<&str> = clean.split('.').collect();
-                let numerator = clean.replace(".", "").trim().parse::<i64>().unwrap_or(1);
-                let denominator = 10_i64.pow(parts[1].len() as u32);
-                Rational64::new(numerator, denominator)
-            } else {
-                clean.trim().parse::<i64>().unwrap_or(1).into()
-            }
-        })
-        .unwrap_or(1.into());
-    polynomial::Variable { name, degree }
+fn parse_assignment(
+    assignment: Pairs<Rule>,
+    ctx: &Context,
+) -> Result<(String, PolyRatio), ParseError> {
+    let mut iter = assignment;
+    let var_name = iter.next().unwrap().as_str().to_string();
+    let value = iter.next().unwrap();
+    let ratio = match value.as_rule() {
+        Rule::operation => parse_operation(value.into_inner(), ctx)?,
+        Rule::polynomial => PolyRatio::from(parse_polynomial(value.into_inner())?),
+        _ => unreachable!(),
+    };
+    Ok((var_name, ratio))
 }
 
-fn parse_polynomial(expression: Pairs<Rule>) -> polynomial::Polynomial {
-    let mut p = polynomial::Polynomial {
-        terms: Vec::new(),
-        degree: 1.into(),
+/// Replaces the parameters of a user function with the arguments of a call to it.
+fn call(
+    name: &str,
+    function: &Function,
+    args: &[PolyRatio],
+    ctx: &Context,
+) -> Result<polynomial::Polynomial, ParseError> {
+    if args.len() != function.params.len() {
+        return Err(ParseError::Invalid(format!(
+            "{} takes {} argument(s) but {} were given",
+            name,
+            function.params.len(),
+            args.len()
+        )));
+    }
+    // Rename the parameters first so that an argument mentioning another parameter is not replaced again
+    let mut body = function.body.clone();
+    let placeholder = |param: &str| polynomial::Polynomial {
+        terms: vec![polynomial::Term {
+            coefficient: Coeff::one(),
+            variables: vec![polynomial::Variable {
+                name: format!("{}'", param),
+                degree: 1.into(),
+            }],
+        }],
+        pending_pow: 1.into(),
     };
-    for part in expression {
-        match part.as_rule() {
-            Rule::term => {
-                let mut term = polynomial::Term {
-                    coefficient: Rational64::new(1, 1),
-                    variables: Vec::new(),
-                };
-                for factor in part.into_inner() {
-                    match factor.as_rule() {
-                        Rule::sign => {
-                            if factor.as_str() == "-" {
-                                term.coefficient *= -1;
-                            }
-                        }
-                        Rule::number => {
-                            term.coefficient *=
-                                factor.as_str().trim().parse::<Rational64>().unwrap();
-                        }
-                        Rule::fraction => {
-                            let mut iter = factor.into_inner();
-                            let numerator =
-                                iter.next().unwrap().as_str().trim().parse::<i64>().unwrap();
-                            let denominator =
-                                iter.next().unwrap().as_str().trim().parse::<i64>().unwrap();
-                            term.coefficient *= Rational64::new(numerator, denominator);
-                        }
-                        Rule::var => {
-                            let variable = variable_from_string(factor.as_str());
-                            term.variables.push(variable);
-                        }
-                        Rule::EOI => (),
-                        _ => unreachable!(),
-                    }
-                }
-                p.terms.push(term);
+    for param in &function.params {
+        body = body.substitute(param, &placeholder(param))?;
+    }
+    for (param, arg) in function.params.iter().zip(args) {
+        let arg = expand_ratio(arg, &ctx.bindings)?;
+        let value = match as_polynomial(&arg) {
+            Some(p) => p,
+            None => {
+                return Err(ParseError::Invalid(format!(
+                    "the arguments of {} must be polynomials, found {}",
+                    name,
+                    arg.as_string()?
+                )));
             }
-            Rule::EOI => (),
-            _ => unreachable!(),
-        }
+        };
+        body = body.substitute(&format!("{}'", param), &value)?;
     }
-    p
+    Ok(body)
 }
 
-fn parse_assignment(assignment: Pairs<Rule>) -> (String, Rational64) {
-    // Only assigning Rational64 values?
-    let mut iter = assignment;
-    let var_name = iter.next().unwrap().as_str().to_string();
-    let var_value = iter
-        .next()
-        .unwrap()
-        .as_str()
-        .trim()
-        .parse::<Rational64>()
-        .unwrap();
-    (var_name, var_value)
+/// Parses a function definition like `f(x) = x^2 + 1`. Parameters are not replaced by the values bound to them.
+fn parse_definition(
+    definition: Pairs<Rule>,
+    ctx: &Context,
+) -> Result<(String, Function), ParseError> {
+    let mut iter = definition.peekable();
+    let name = iter.next().unwrap().as_str().to_string();
+    let mut params: Vec<String> = Vec::new();
+    while let Some(pair) = iter.next_if(|p| p.as_rule() == Rule::var_name) {
+        let param = pair.as_str().to_string();
+        if params.contains(&param) {
+            return Err(ParseError::Invalid(format!(
+                "parameter {} of {} appears twice",
+                param, name
+            )));
+        }
+        params.push(param);
+    }
+    // A call to the function itself would be taken as an earlier definition of it or as a product, so look for one
+    // before parsing the body
+    let body = iter.next().unwrap();
+    if body
+        .clone()
+        .into_inner()
+        .flatten()
+        .any(|p| p.as_rule() == Rule::call && p.into_inner().next().unwrap().as_str() == name)
+    {
+        return Err(ParseError::Invalid(format!(
+            "recursive definition of {}",
+            name
+        )));
+    }
+    let mut local = ctx.clone();
+    local.bindings.retain(|(n, _)| !params.contains(n));
+    let value = expand_ratio(
+        &parse_operation(body.into_inner(), &local)?,
+        &local.bindings,
+    )?;
+    let body = match as_polynomial(&value) {
+        Some(p) => p,
+        None => {
+            return Err(ParseError::Invalid(format!(
+                "{} can only be defined as a polynomial, not {}",
+                name,
+                value.as_string()?
+            )));
+        }
+    };
+    if body.variables().contains(&name) {
+        return Err(ParseError::Invalid(format!(
+            "recursive definition of {}",
+            name
+        )));
+    }
+    Ok((name, Function { params, body }))
 }
 
-fn parse_operation(operation: Pairs<Rule>) -> polynomial::PolyRatio {
-    let mut iter = operation;
-    let first_poly = parse_polynomial(iter.next().unwrap().into_inner());
-    let mut result = PolyRatio::from(first_poly);
+/// A value bound to a name by an assignment.
+#[derive(Clone)]
+enum Binding {
+    Number(Coeff),
+    Poly(polynomial::Polynomial),
+}
 
-    while let Some(op) = iter.next() {
-        let next_poly = parse_polynomial(iter.next().unwrap().into_inner());
-        match op.as_rule() {
-            Rule::add => result = result + next_poly,
-            Rule::sub => result = result - next_poly,
-            Rule::mul => result = result * next_poly,
-            Rule::div => result = result / next_poly,
-            _ => unreachable!(),
-        }
-    }
-    result
+/// A function defined by the user, like `f(x, y) = x^2 + y`.
+#[derive(Clone)]
+struct Function {
+    params: Vec<String>,
+    body: polynomial::Polynomial,
 }
 
-fn main() {
-    /*
-    let t = polynomial::Term {
-        coefficient: Rational64::new(-13, 1),
-        variables: vec![],
-    };
-    let mut tp = t.pow(Rational64::new(1, 2));
-    println!("{}", tp.as_string());
-    tp.simplify();
-    println!("{}", tp.as_string());
-    */
+/// State carried from one statement of the input file to the next.
+#[derive(Clone)]
+struct Context {
+    bindings: Vec<(String, Binding)>,
+    functions: Vec<(String, Function)>,
+    last_result: Option<PolyRatio>,
+    /// Skip lines that fail to parse instead of stopping.
+    keep_going: bool,
+    /// Print results apart as their polynomial part plus a proper fraction.
+    mixed: bool,
+    /// A line failed to parse or a statement failed to run, so the run exits with an error status.
+    failed: bool,
+}
 
-    // println!("{}", (-13_f64).powf(0.5));
+impl Scope for Context {
+    fn last_result(&self) -> Option<PolyRatio> {
+        self.last_result.clone()
+    }
 
-    // Test simplify fractions
-    // let mut p = Polynomial {
-    //     terms: vec![
-    //         polynomial::Term {
-    //             coefficient: Rational64::new(3, 1),
-    //             variables: vec![polynomial::Variable {
-    //                 name: "x".to_string(),
-    //                 degree: -1,
-    //             }],
-    //         },
-    //         polynomial::Term {
-    //             coefficient: Rational64::new(4, 1),
-    //             variables: vec![polynomial::Variable {
-    //                 name: "x".to_string(),
-    //                 degree: 1,
-    //             }],
-    //         },
-    //     ],
-    // };
-    // p.simplify();
-    // println!("Polynomial p: {}", p.as_string());
+    fn call(
+        &self,
+        name: &str,
+        args: &[PolyRatio],
+    ) -> Option<Result<polynomial::Polynomial, ParseError>> {
+        let (_, function) = self.functions.iter().find(|(n, _)| n == name)?;
+        Some(call(name, function, args, self))
+    }
 
-    // let mut q = Polynomial {
-    //     terms: vec![polynomial::Term {
-    //         coefficient: Rational64::new(6, 1),
-    //         variables: vec![polynomial::Variable {
-    //             name: "x".to_string(),
-    //             degree: 1,
-    //         }],
-    //     }],
-    // };
+    fn expand(&self, ratio: &PolyRatio) -> Result<PolyRatio, ParseError> {
+        expand_ratio(ratio, &self.bindings)
+    }
+}
 
-    // q.simplify();
-    // println!("Polynomial q: {}", q.as_string());
+/// Returns the names bound to numbers along with their values.
+#[cfg_attr(not(feature = "bigrational"), allow(clippy::clone_on_copy))]
+fn numeric_values(bindings: &[(String, Binding)]) -> Vec<(String, Coeff)> {
+    bindings
+        .iter()
+        .filter_map(|(name, binding)| match binding {
+            Binding::Number(value) => Some((name.clone(), value.clone())),
+            Binding::Poly(_) => None,
+        })
+        .collect()
+}
 
-    // let mut r = PolyRatio {
-    //     numerator: p,
-    //     denominator: q,
-    // };
+/// Substitutes the names bound to polynomials.
+fn substitute_bindings(
+    p: &polynomial::Polynomial,
+    bindings: &[(String, Binding)],
+) -> Result<polynomial::Polynomial, ParseError> {
+    let mut result = p.clone();
+    for (name, binding) in bindings {
+        if let Binding::Poly(value) = binding {
+            result = result.substitute(name, value)?;
+        }
+    }
+    Ok(result)
+}
 
-    // println!("Ratio: {}", r.as_string());
+/// Substitutes the names bound to polynomials and evaluates the ones bound to numbers.
+fn expand_bindings(
+    p: &polynomial::Polynomial,
+    bindings: &[(String, Binding)],
+) -> Result<polynomial::Polynomial, ParseError> {
+    let mut result = substitute_bindings(p, bindings)?;
+    result.evaluate(&numeric_values(bindings))?;
+    Ok(result)
+}
 
-    // r.simplify();
-    // println!("ratio (simplified): {}", r.as_string());
+/// Expands the bindings in both parts of the ratio. Values that make the denominator zero are an error.
+fn expand_ratio(
+    ratio: &PolyRatio,
+    bindings: &[(String, Binding)],
+) -> Result<PolyRatio, ParseError> {
+    let mut result = PolyRatio {
+        numerator: substitute_bindings(&ratio.numerator, bindings)?,
+        denominator: substitute_bindings(&ratio.denominator, bindings)?,
+    };
+    let values = numeric_values(bindings);
+    let mut vars = result.numerator.vars();
+    vars.extend(result.denominator.vars());
+    result.evaluate(&values)?;
+    PolyRatio::new(result.numerator, result.denominator).map_err(|e| match e {
+        // Name the values that made the denominator zero
+        PolyError::DivisionByZero { .. } => ParseError::DivisionByZero {
+            at: values
+                .into_iter()
+                .filter(|(var, _)| vars.contains(var))
+                .collect(),
+        },
+        e => e.into(),
+    })
+}
 
-    // Test squared polynomial
-    // let mut not_ab_squared = polynomial::Polynomial {
-    //     terms: vec![
-    //         polynomial::Term {
-    //             coefficient: Rational64::new(1, 1),
-    //             variables: vec![polynomial::Variable {
-    //                 name: "a".to_string(),
-    //                 degree: 1.into(),
-    //             }],
-    //         },
-    //         polynomial::Term {
-    //             coefficient: Rational64::new(1, 1),
-    //             variables: vec![polynomial::Variable {
-    //                 name: "b".to_string(),
-    //                 degree: 1.into(),
-    //             }],
-    //         },
-    //     ],
-    //     degree: 2.into(),
-    // };
-    // not_ab_squared.simplify();
-    // println!("(a + b)^2 = {}", not_ab_squared.as_string());
+/// Converts a result to a string, split into its polynomial part and a proper fraction if `ctx.mixed` is set.
+fn show(ratio: &PolyRatio, ctx: &Context) -> Result<String, PolyError> {
+    if ctx.mixed {
+        ratio.as_string_mixed()
+    } else {
+        ratio.as_string()
+    }
+}
 
-    let unparsed_file = fs::read_to_string("input.txt").unwrap();
+/// Returns the variable given to a command on a ratio, or its only variable if none was given.
+/// `what` is what the command finds, for the error on a constant.
+fn ratio_variable(
+    ratio: &PolyRatio,
+    var: Option<Pair<Rule>>,
+    what: &str,
+) -> Result<String, ParseError> {
+    if let Some(var) = var {
+        return Ok(var.as_str().to_string());
+    }
+    let mut vars = ratio.numerator.vars();
+    vars.extend(ratio.denominator.vars());
+    match vars.len() {
+        0 => Err(ParseError::Invalid(format!("a constant has no {}", what))),
+        1 => Ok(vars.into_iter().next().unwrap()),
+        _ => Err(ParseError::Invalid(format!(
+            "the expression has several variables ({}), add the one to use after a comma",
+            vars.into_iter().collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
 
-    let file = PolyParser::parse(Rule::file, &unparsed_file)
-        .expect("unsuccessful parse")
-        .next()
-        .unwrap();
+/// Writes a root given as a sum of parts, like the ones from `roots`.
+fn root_string(root: &[PolyRatio]) -> Result<String, PolyError> {
+    let mut result = root[0].as_string()?;
+    for ratio in &root[1..] {
+        let part = ratio.as_string()?;
+        if !part.starts_with('-') {
+            result.push_str(" + ");
+        }
+        result.push_str(&part);
+    }
+    Ok(result)
+}
 
-    let mut var_values: Vec<(String, Rational64)> = Vec::new(); // Vector to store the values of the variables
+/// Evaluates an operation used as the argument of a command, which must simplify to a polynomial.
+fn polynomial_argument(
+    operation: Pair<Rule>,
+    ctx: &Context,
+) -> Result<polynomial::Polynomial, ParseError> {
+    let ratio = expand_ratio(
+        &parse_operation(operation.into_inner(), ctx)?,
+        &ctx.bindings,
+    )?;
+    match as_polynomial(&ratio) {
+        Some(p) => Ok(p),
+        None => Err(ParseError::Invalid(format!(
+            "expected a polynomial, found {}",
+            ratio.as_string()?
+        ))),
+    }
+}
 
-    for line in file.into_inner() {
-        if line.as_str().trim().is_empty() {
-            continue; // Skip empty lines
+/// Binds `var_name` to `value`, which must simplify to a polynomial that does not contain `var_name`.
+fn bind(
+    var_name: String,
+    value: &PolyRatio,
+    bindings: &mut Vec<(String, Binding)>,
+) -> Result<String, ParseError> {
+    let ratio = expand_ratio(value, bindings)?;
+    let p = match as_polynomial(&ratio) {
+        Some(p) => p,
+        None => {
+            return Err(ParseError::Invalid(format!(
+                "{} can only be assigned a polynomial, not {}",
+                var_name,
+                ratio.as_string()?
+            )));
         }
+    };
+    if p.terms
+        .iter()
+        .any(|t| t.variables.iter().any(|v| v.name == var_name))
+    {
+        return Err(ParseError::Invalid(format!(
+            "recursive definition of {}",
+            var_name
+        )));
+    }
 
-        println!("{}", line.as_str());
-        match line.as_rule() {
-            Rule::assign => {
-                let (var_name, var_value) = parse_assignment(line.into_inner());
-                var_values.push((var_name.clone(), var_value));
+    let binding = match constant_value(&p) {
+        Some(value) => Binding::Number(value),
+        None => Binding::Poly(p),
+    };
+    let shown = match &binding {
+        Binding::Number(value) => value.to_string(),
+        Binding::Poly(p) => p.as_string(),
+    };
+    match bindings.iter_mut().find(|(name, _)| *name == var_name) {
+        Some(entry) => entry.1 = binding,
+        None => bindings.push((var_name, binding)),
+    }
+    Ok(shown)
+}
 
-                println!("\t{} = {}", var_name, var_value);
+fn run_statement(line: Pair<Rule>, ctx: &mut Context) -> Result<(), ParseError> {
+    match line.as_rule() {
+        Rule::define => {
+            let (name, function) = parse_definition(line.into_inner(), ctx)?;
+            println!(
+                "\t{}({}) = {}",
+                name,
+                function.params.join(", "),
+                function.body.as_string()
+            );
+            match ctx.functions.iter_mut().find(|(n, _)| *n == name) {
+                Some(entry) => entry.1 = function,
+                None => ctx.functions.push((name, function)),
+            }
+        }
+        Rule::unset => {
+            for name in line.into_inner() {
+                let name = name.as_str();
+                let before = ctx.bindings.len() + ctx.functions.len();
+                ctx.bindings.retain(|(n, _)| n != name);
+                ctx.functions.retain(|(n, _)| n != name);
+                if ctx.bindings.len() + ctx.functions.len() == before {
+                    eprintln!("\tWarning: {} is not bound", name);
+                }
             }
-            Rule::polynomial => {
-                let mut p = parse_polynomial(line.into_inner());
-                p.evaluate(&var_values);
+        }
+        Rule::clear => {
+            ctx.bindings.clear();
+            ctx.functions.clear();
+        }
+        Rule::assign => {
+            // Each pair is bound before the next one is read, so later values can use earlier names
+            let mut assigned: Vec<String> = Vec::new();
+            for pair in line.into_inner() {
+                let (var_name, value) = parse_assignment(pair.into_inner(), ctx)?;
+                if assigned.contains(&var_name) {
+                    eprintln!(
+                        "\tWarning: {} is assigned more than once, the last value is kept",
+                        var_name
+                    );
+                }
+                let shown = bind(var_name.clone(), &value, &mut ctx.bindings)?;
+                println!("\t{} = {}", var_name, shown);
+                assigned.push(var_name);
+            }
+        }
+        Rule::polynomial => {
+            let p = expand_bindings(&parse_polynomial(line.into_inner())?, &ctx.bindings)?;
+            println!("\t{}", p.as_string());
+            ctx.last_result = Some(PolyRatio::from(p));
+        }
+        Rule::operation => {
+            let result = expand_ratio(&parse_operation(line.into_inner(), ctx)?, &ctx.bindings)?;
+            println!("\t{}", show(&result, ctx)?);
+            ctx.last_result = Some(result);
+        }
+        Rule::simplify => {
+            let operation = line.into_inner().next().unwrap();
+            let mut result = expand_ratio(
+                &parse_operation(operation.into_inner(), ctx)?,
+                &ctx.bindings,
+            )?;
+            result.simplify();
+            println!("\t{}", show(&result, ctx)?);
+            ctx.last_result = Some(result);
+        }
+        Rule::expand => {
+            let operation = line.into_inner().next().unwrap();
+            let result = expand_ratio(
+                &parse_operation(operation.into_inner(), ctx)?,
+                &ctx.bindings,
+            )?;
+            match as_polynomial(&result) {
+                Some(p) => println!("\t{}", p.as_string()),
+                None => println!("\t{}", result.as_string()?),
+            }
+            ctx.last_result = Some(result);
+        }
+        Rule::factor => {
+            let mut p = polynomial_argument(line.into_inner().next().unwrap(), ctx)?;
+            if let Some(var) = p.is_univariate() {
+                let (mut content, rational) = p.factor_rational(&var);
+                // Factors without rational roots may still split into factors of higher degree, like
+                // x^4+4 = (x^2-2x+2)(x^2+2x+2)
+                let mut factors: Vec<(polynomial::Polynomial, u32)> = Vec::new();
+                for (factor, multiplicity) in rational {
+                    let pieces = if factor.degree_in(&var) > 1.into() {
+                        factor.factor_kronecker(&var)
+                    } else {
+                        vec![factor]
+                    };
+                    for piece in pieces {
+                        if piece.is_constant() {
+                            for _ in 0..multiplicity {
+                                content *= &piece.terms[0].coefficient;
+                            }
+                            continue;
+                        }
+                        match factors.iter_mut().find(|(f, _)| *f == piece) {
+                            Some((_, m)) => *m += multiplicity,
+                            None => factors.push((piece, multiplicity)),
+                        }
+                    }
+                }
+                if content.is_one() && factors.len() == 1 && factors[0].1 == 1 {
+                    println!("\t{}", p.as_string());
+                } else {
+                    let mut printed = match content {
+                        c if c.is_one() => String::new(),
+                        c if c == -Coeff::one() => "-".to_string(),
+                        c => polynomial::Term::constant(c).as_string(),
+                    };
+                    for (factor, multiplicity) in &factors {
+                        if factor.terms.len() == 1 {
+                            printed.push_str(&factor.as_string());
+                        } else {
+                            printed.push_str(&format!("({})", factor.as_string()));
+                        }
+                        if *multiplicity > 1 {
+                            printed.push_str(&format!("^({})", multiplicity));
+                        }
+                    }
+                    println!("\t{}", printed);
+                }
+                ctx.last_result = Some(PolyRatio::from(p));
+                return Ok(());
+            }
+            let (factored_out, factored) = p.factor();
+            let trivial = factored_out.variables.is_empty()
+                && (factored_out.coefficient.is_one() || factored_out.coefficient.is_zero());
+            if trivial {
                 println!("\t{}", p.as_string());
-                // println!("{:?}", p);
+            } else {
+                println!("\t{}({})", factored_out.as_string(), factored.as_string());
             }
-            Rule::operation => {
-                let mut result = parse_operation(line.into_inner());
-                result.evaluate(&var_values);
-                println!("\t{}", result.as_string());
-                // println!("{:?}", result);
+            ctx.last_result = Some(PolyRatio::from(p));
+        }
+        Rule::gcd => {
+            let mut iter = line.into_inner();
+            let p = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let q = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let result = p.gcd(&q);
+            println!("\t{}", result.as_string());
+            ctx.last_result = Some(PolyRatio::from(result));
+        }
+        Rule::modulo => {
+            let mut iter = line.into_inner();
+            let p = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let modulus: Rational64 = parse_number(iter.next().unwrap().as_str())?;
+            if !modulus.is_integer() {
+                return Err(ParseError::Invalid(format!(
+                    "the modulus must be a whole number, found {}",
+                    modulus
+                )));
+            }
+            let result = p.mod_p(modulus.to_integer())?;
+            println!("\t{}", result.as_string());
+            ctx.last_result = Some(PolyRatio::from(result));
+        }
+        Rule::deg => {
+            let mut iter = line.into_inner();
+            let p = polynomial_argument(iter.next().unwrap(), ctx)?;
+            // The zero polynomial has no degree
+            let degree = match iter.next() {
+                Some(var) => (!p.is_zero()).then(|| p.degree_in(var.as_str())),
+                None => p.degree(),
+            };
+            if let Some(degree) = degree {
+                println!("\t{}", degree);
+                ctx.last_result = Some(PolyRatio::from(polynomial::Polynomial {
+                    terms: vec![polynomial::Term {
+                        coefficient: polynomial::degree_coeff(degree),
+                        variables: vec![],
+                    }],
+                    pending_pow: 1.into(),
+                }));
+            } else {
+                println!("\t-inf");
             }
-            Rule::solve => {
-                let mut iter = line.into_inner();
-                let mut p = parse_polynomial(iter.next().unwrap().into_inner());
-                p.evaluate(&var_values);
-                let mut variable = p.first_var().unwrap_or("".to_string());
-                if variable.is_empty() {
-                    panic!("No variable to solve for");
+        }
+        Rule::disc => {
+            let mut iter = line.into_inner();
+            let p = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let var = match iter.next() {
+                Some(var) => var.as_str().to_string(),
+                None => {
+                    let vars = p.vars();
+                    match vars.len() {
+                        0 => return Err(ParseError::Invalid("a constant has no discriminant".to_string())),
+                        1 => vars.into_iter().next().unwrap(),
+                        _ => {
+                            return Err(ParseError::Invalid(format!(
+                                "the polynomial has several variables ({}), add the one to use after a comma",
+                                vars.into_iter().collect::<Vec<_>>().join(", ")
+                            )))
+                        }
+                    }
+                }
+            };
+            let discriminant = p.discriminant(&var)?;
+            println!("\t{}", discriminant.as_string());
+            ctx.last_result = Some(PolyRatio::from(discriminant));
+        }
+        Rule::partfrac => {
+            let mut iter = line.into_inner();
+            let ratio = expand_ratio(
+                &parse_operation(iter.next().unwrap().into_inner(), ctx)?,
+                &ctx.bindings,
+            )?;
+            let var = ratio_variable(&ratio, iter.next(), "partial fractions")?;
+            let pieces = ratio.partial_fractions(&var)?;
+            let strings = pieces
+                .iter()
+                .map(|piece| piece.as_string())
+                .collect::<Result<Vec<_>, _>>()?;
+            println!(
+                "\t{}",
+                if strings.is_empty() {
+                    "0".to_string()
+                } else {
+                    strings.join(" + ")
+                }
+            );
+            ctx.last_result = Some(ratio);
+        }
+        Rule::limit => {
+            let mut iter = line.into_inner();
+            let ratio = expand_ratio(
+                &parse_operation(iter.next().unwrap().into_inner(), ctx)?,
+                &ctx.bindings,
+            )?;
+            let var = iter.next().unwrap().as_str();
+            let point = iter.next().unwrap();
+            let limit = match point.as_rule() {
+                Rule::infinity => ratio.limit_at_infinity(var),
+                _ => {
+                    let value = polynomial_argument(point, ctx)?;
+                    let a = constant_value(&value).ok_or_else(|| {
+                        ParseError::Invalid(format!(
+                            "the point of a limit must be a number, found {}",
+                            value.as_string()
+                        ))
+                    })?;
+                    ratio.limit_at(var, a)
                 }
-                if let Some(var) = iter.next() {
-                    // Variable was specified
-                    variable = var.as_str().to_string();
+            };
+            println!("\t{}", limit);
+            if let LimitResult::Finite(value) = limit {
+                ctx.last_result = Some(PolyRatio::from(polynomial::Polynomial::constant(value)));
+            }
+        }
+        Rule::asymptotes => {
+            let mut iter = line.into_inner();
+            let ratio = expand_ratio(
+                &parse_operation(iter.next().unwrap().into_inner(), ctx)?,
+                &ctx.bindings,
+            )?;
+            let var = ratio_variable(&ratio, iter.next(), "asymptotes")?;
+            let asymptotes = ratio.asymptotes(&var)?;
+            let vertical = asymptotes
+                .vertical
+                .iter()
+                .map(|root| Ok(format!("{} = {}", var, root_string(root)?)))
+                .collect::<Result<Vec<_>, PolyError>>()?;
+            let none = || "none".to_string();
+            println!(
+                "\tvertical:\t{}",
+                if vertical.is_empty() {
+                    none()
+                } else {
+                    vertical.join(", ")
                 }
-                // println!("Solving for {}...", variable);
-                let result = p.roots(&variable);
-                for root in result {
-                    if root.len() == 1 {
-                        println!("\t{}\t= {}", variable, root[0].as_string());
-                    } else if root.len() > 1 {
-                        print!("\t{}\t= {}", variable, root[0].as_string());
-                        for ratio in &root[1..] {
-                            if ratio.numerator.terms[0].coefficient >= 0.into() {
-                                print!(" + ");
+            );
+            println!(
+                "\thorizontal:\t{}",
+                asymptotes
+                    .horizontal
+                    .map_or_else(none, |c| format!("y = {}", c))
+            );
+            println!(
+                "\toblique:\t{}",
+                asymptotes
+                    .oblique
+                    .map_or_else(none, |p| format!("y = {}", p.as_string()))
+            );
+        }
+        Rule::domain => {
+            let operation = line.into_inner().next().unwrap();
+            let ratio = expand_ratio(
+                &parse_operation(operation.clone().into_inner(), ctx)?,
+                &ctx.bindings,
+            )?;
+            // What the expression divides by can be zero or undefined even where its value is not, once it cancels out
+            let mut parts = vec![ratio];
+            for divisor in divisors(operation.into_inner(), ctx)? {
+                let divisor = expand_ratio(&divisor, &ctx.bindings)?;
+                parts.push(PolyRatio {
+                    numerator: divisor.denominator.clone(),
+                    denominator: divisor.numerator.clone(),
+                });
+                parts.push(divisor);
+            }
+            let mut vars: BTreeSet<String> = BTreeSet::new();
+            for part in &parts {
+                vars.extend(part.numerator.vars());
+                vars.extend(part.denominator.vars());
+            }
+            if vars.is_empty() {
+                println!("\tall reals");
+            }
+            for var in vars {
+                let mut excluded: Vec<Vec<PolyRatio>> = Vec::new();
+                let mut curve = None;
+                for part in &parts {
+                    match part.excluded_values(&var) {
+                        Ok(values) => {
+                            for value in values {
+                                if !excluded.contains(&value) {
+                                    excluded.push(value);
+                                }
                             }
-                            print!("{}", ratio.as_string());
                         }
-                        println!();
+                        Err(PolyError::NotUnivariate { other, .. }) => curve = Some(other),
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                if let Some(other) = curve {
+                    println!(
+                        "\t{}:\tthe excluded values depend on {}, so they form a curve rather than single values",
+                        var, other
+                    );
+                } else if excluded.is_empty() {
+                    println!("\t{}:\tall reals", var);
+                } else {
+                    let values = excluded
+                        .iter()
+                        .map(|root| root_string(root))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    println!("\t{}:\texcluding {}", var, values.join(", "));
+                }
+            }
+        }
+        Rule::vars => {
+            let p = polynomial_argument(line.into_inner().next().unwrap(), ctx)?;
+            let vars: Vec<String> = p.vars().into_iter().collect();
+            println!("\t{{{}}}", vars.join(", "));
+        }
+        Rule::subs => {
+            let mut iter = line.into_inner();
+            let operation = iter.next().unwrap();
+            let mut result = expand_ratio(
+                &parse_operation(operation.into_inner(), ctx)?,
+                &ctx.bindings,
+            )?;
+            // Replacements are applied from left to right, on the ratio once the expression or a value is not a polynomial
+            for replacement in iter {
+                let mut inner = replacement.into_inner();
+                let var = inner.next().unwrap().as_str();
+                let value = expand_ratio(
+                    &parse_operation(inner.next().unwrap().into_inner(), ctx)?,
+                    &ctx.bindings,
+                )?;
+                result = match (as_polynomial(&result), as_polynomial(&value)) {
+                    // Negative powers of var may not give a polynomial, but still give a ratio
+                    (Some(p), Some(value)) => match p.substitute(var, &value) {
+                        Ok(p) => PolyRatio::from(p),
+                        Err(_) => result.substitute(var, &value)?,
+                    },
+                    (None, Some(value)) => result.substitute(var, &value)?,
+                    (_, None) => result.substitute_ratio(var, &value)?,
+                };
+            }
+            match as_polynomial(&result) {
+                Some(p) => println!("\t{}", p.as_string()),
+                None => println!("\t{}", result.as_string()?),
+            }
+            ctx.last_result = Some(result);
+        }
+        Rule::diff => {
+            let mut iter = line.into_inner();
+            let p = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let var = iter.next().unwrap().as_str();
+            let order = match iter.next() {
+                Some(order) => {
+                    let n: Rational64 = parse_number(order.as_str())?;
+                    if !n.is_integer() || n < 0.into() {
+                        return Err(ParseError::Invalid(format!(
+                            "the order of a derivative must be a whole number, found {}",
+                            n
+                        )));
+                    }
+                    u32::try_from(n.to_integer()).map_err(|_| {
+                        ParseError::Invalid(format!(
+                            "the order of a derivative is too large, found {}, the limit is {}",
+                            n,
+                            u32::MAX
+                        ))
+                    })?
+                }
+                None => 1,
+            };
+            let result = p.nth_derivative(var, order);
+            println!("\t{}", result.as_string());
+            ctx.last_result = Some(PolyRatio::from(result));
+        }
+        Rule::integrate => {
+            let mut iter = line.into_inner();
+            let p = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let var = iter.next().unwrap().as_str();
+            let result = match (iter.next(), iter.next()) {
+                (Some(a), Some(b)) => {
+                    let bound = |pair| -> Result<Coeff, ParseError> {
+                        let value = polynomial_argument(pair, ctx)?;
+                        constant_value(&value).ok_or_else(|| {
+                            ParseError::Invalid(format!(
+                                "the bounds of an integral must be numbers, found {}",
+                                value.as_string()
+                            ))
+                        })
+                    };
+                    let (a, b) = (bound(a)?, bound(b)?);
+                    p.definite_integral(var, a, b)
+                }
+                _ => p.integral(var),
+            }?;
+            println!("\t{}", result.as_string());
+            ctx.last_result = Some(PolyRatio::from(result));
+        }
+        Rule::compose => {
+            let mut iter = line.into_inner();
+            let p = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let q = polynomial_argument(iter.next().unwrap(), ctx)?;
+            let result = match iter.next() {
+                Some(var) => p.compose(var.as_str(), &q),
+                None => match p.first_var() {
+                    Some(var) => p.compose(&var, &q),
+                    None => Ok(p),
+                },
+            }?;
+            println!("\t{}", result.as_string());
+            ctx.last_result = Some(PolyRatio::from(result));
+        }
+        Rule::interpolate => {
+            let number = |pair| -> Result<Coeff, ParseError> {
+                let value = polynomial_argument(pair, ctx)?;
+                constant_value(&value).ok_or_else(|| {
+                    ParseError::Invalid(format!(
+                        "the points to interpolate must be numbers, found {}",
+                        value.as_string()
+                    ))
+                })
+            };
+            let mut points = Vec::new();
+            let mut var = "x".to_string();
+            for pair in line.into_inner() {
+                match pair.as_rule() {
+                    Rule::point => {
+                        let mut inner = pair.into_inner();
+                        let x = number(inner.next().unwrap())?;
+                        let y = number(inner.next().unwrap())?;
+                        points.push((x, y));
+                    }
+                    _ => var = pair.as_str().to_string(),
+                }
+            }
+            let result = polynomial::Polynomial::interpolate(&var, &points)?;
+            println!("\t{}", result.as_string());
+            ctx.last_result = Some(PolyRatio::from(result));
+        }
+        Rule::solve => {
+            let mut iter = line.into_inner();
+            let mut equation = iter.next().unwrap().into_inner();
+            let lhs = parse_operation(equation.next().unwrap().into_inner(), ctx)?;
+            // lhs = rhs is solved as lhs - rhs = 0
+            let difference = match equation.next() {
+                Some(rhs) => lhs - parse_operation(rhs.into_inner(), ctx)?,
+                None => lhs,
+            };
+            let p = expand_ratio(&difference, &ctx.bindings)?.numerator;
+            if let Some(value) = constant_value(&p) {
+                if value.is_zero() {
+                    println!("\tinfinitely many solutions");
+                } else {
+                    println!("\tno solution");
+                }
+                return Ok(());
+            }
+            let variable = match iter.next() {
+                // Variable was specified
+                Some(var) => var.as_str().to_string(),
+                None => {
+                    let vars = p.vars();
+                    match vars.len() {
+                        0 => return Err(ParseError::Invalid("No variable to solve for".to_string())),
+                        1 => vars.into_iter().next().unwrap(),
+                        _ => {
+                            return Err(ParseError::Invalid(format!(
+                                "the equation has several variables ({}), add the one to solve for after a comma",
+                                vars.into_iter().collect::<Vec<_>>().join(", ")
+                            )))
+                        }
+                    }
+                }
+            };
+            // A repeated root is solved once from its square-free factor and reported with its multiplicity
+            let factors = p.squarefree_factorization(&variable);
+            let mut result = Vec::new();
+            if factors.iter().any(|(_, multiplicity)| *multiplicity > 1) {
+                for (factor, multiplicity) in factors {
+                    for root in factor.roots(&variable)? {
+                        result.push((root, multiplicity));
                     }
-                    // println!("\t{}", r.as_string());
                 }
-                // panic!("Not implemented");
+            } else {
+                for root in p.roots(&variable)? {
+                    result.push((root, 1));
+                }
+            }
+            // Only a rational root can be reused as ans
+            ctx.last_result = result
+                .iter()
+                .map(|(root, _)| root)
+                .find(|root| !root.is_empty())
+                .filter(|root| root.len() == 1)
+                .map(|root| root[0].clone());
+            for (root, multiplicity) in result {
+                if root.is_empty() {
+                    continue;
+                }
+                print!("\t{}\t= {}", variable, root_string(&root)?);
+                if multiplicity > 1 {
+                    print!("\t(multiplicity {})", multiplicity);
+                }
+                println!();
+            }
+        }
+        Rule::EOI => (),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Parses a file line by line and runs its statements in order. `including` holds the files that are being run, to detect include cycles.
+/// Lines that fail to parse stop the file before anything runs, unless `ctx.keep_going` is set, in which case they are reported and skipped.
+fn run_file(
+    path: &Path,
+    ctx: &mut Context,
+    including: &mut Vec<PathBuf>,
+) -> Result<(), ParseError> {
+    let unparsed_file = fs::read_to_string(path)
+        .map_err(|e| ParseError::Invalid(format!("cannot read {}: {}", path.display(), e)))?;
+
+    let mut lines = Vec::new();
+    for (offset, line) in split_lines(&unparsed_file) {
+        match PolyParser::parse(Rule::input_line, line) {
+            Ok(mut pairs) => lines.push(Ok(pairs.next().unwrap().into_inner().next().unwrap())),
+            Err(e) => {
+                ctx.failed = true;
+                let e = locate(e, &unparsed_file, offset).with_path(&path.display().to_string());
+                if !ctx.keep_going {
+                    return Err(e.into());
+                }
+                lines.push(Err(e));
             }
-            Rule::EOI => (),
-            _ => unreachable!(),
         }
     }
+
+    including.push(fs::canonicalize(path).unwrap_or(path.to_path_buf()));
+    for (n, line) in lines.into_iter().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+        let statements: Vec<Pair<Rule>> = line.into_inner().collect();
+        for (i, statement) in statements.iter().enumerate() {
+            println!("{}", statement.as_str().trim());
+            let result = polynomial::catch_overflow(|| match statement.as_rule() {
+                Rule::include => include(statement.clone(), path, n + 1, ctx, including),
+                _ => run_statement(statement.clone(), ctx),
+            })
+            .unwrap_or_else(|e| Err(e.into()))
+            // Overflows and divisions by zero can come from values bound on earlier lines, so say where they happened
+            .map_err(|e| match e {
+                ParseError::Poly(PolyError::CoefficientOverflow)
+                | ParseError::DivisionByZero { .. } => {
+                    ParseError::Invalid(format!("{} on line {} of {}", e, n + 1, path.display()))
+                }
+                e => e,
+            });
+            if let Err(e) = result {
+                ctx.failed = true;
+                if statements.len() > 1 {
+                    eprintln!("\tError in statement {}: {}", i + 1, e);
+                } else {
+                    eprintln!("\tError: {}", e);
+                }
+            }
+        }
+    }
+    including.pop();
+    Ok(())
+}
+
+/// Splits the input into lines ending in `\n`, `\r\n` or `\r`, along with the byte offset where each line starts.
+/// The last line does not need a line ending.
+fn split_lines(input: &str) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\n' || c == '\r' {
+            lines.push((start, &input[start..i]));
+            if c == '\r' && chars.next_if(|&(_, c)| c == '\n').is_some() {
+                start = i + 2;
+            } else {
+                start = i + 1;
+            }
+        }
+    }
+    lines.push((start, &input[start..]));
+    lines
+}
+
+/// Moves a parse error on a single line, which starts at byte `offset` of `input`, to its place in the whole input.
+fn locate(e: pest::error::Error<Rule>, input: &str, offset: usize) -> pest::error::Error<Rule> {
+    let position = |pos: usize| pest::Position::new(input, offset + pos).unwrap();
+    match e.location {
+        InputLocation::Pos(pos) => pest::error::Error::new_from_pos(e.variant, position(pos)),
+        InputLocation::Span((start, end)) => {
+            pest::error::Error::new_from_span(e.variant, position(start).span(&position(end)))
+        }
+    }
+}
+
+/// Runs the file named by an include statement in place. Relative paths are resolved against the including file.
+fn include(
+    statement: Pair<Rule>,
+    from: &Path,
+    line: usize,
+    ctx: &mut Context,
+    including: &mut Vec<PathBuf>,
+) -> Result<(), ParseError> {
+    let string = statement.into_inner().next().unwrap();
+    let name = string.into_inner().next().unwrap().as_str();
+    let path = from.parent().unwrap_or(Path::new("")).join(name);
+    let located = |e: String| {
+        ParseError::Invalid(format!(
+            "{} (included from {}, line {})",
+            e,
+            from.display(),
+            line
+        ))
+    };
+
+    let canonical = fs::canonicalize(&path)
+        .map_err(|e| located(format!("cannot read {}: {}", path.display(), e)))?;
+    if including.contains(&canonical) {
+        return Err(located(format!(
+            "include cycle, {} ends up including itself",
+            path.display()
+        )));
+    }
+    run_file(&path, ctx, including).map_err(|e| located(e.to_string()))
+}
+
+fn main() {
+    let mut ctx = Context {
+        bindings: Vec::new(),
+        functions: Vec::new(),
+        last_result: None,
+        keep_going: std::env::args().any(|arg| arg == "--keep-going"),
+        mixed: std::env::args().any(|arg| arg == "--mixed"),
+        failed: false,
+    };
+
+    // Overflows are reported as errors by `catch_overflow`, so don't print them as panics
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !polynomial::is_overflow(info.payload()) {
+            default_hook(info);
+        }
+    }));
+
+    if let Err(e) = run_file(Path::new("input.txt"), &mut ctx, &mut Vec::new()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if ctx.failed {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,537 @@
+//! Parsing of polynomials and expressions with the grammar in `poly.pest`, which also describes the commands of the
+//! interpreter. Expressions reach the previous result, user functions and bound variables through a `Scope`.
+
+use crate::polynomial::{self, Coeff, PolyError, PolyRatio};
+use num::rational::Ratio;
+use num::{CheckedMul, FromPrimitive, Integer, One, Signed, Zero};
+use pest::iterators::{Pair, Pairs};
+use pest::Parser;
+use pest_derive::Parser;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Parser)]
+#[grammar = "poly.pest"]
+pub struct PolyParser;
+
+/// The largest power of ten a number literal can scale its digits by, like 10^-4096 in 1.5e-4095. Even arbitrary
+/// precision coefficients would take too long to build powers like the 10^2000000000 of 1e2000000000.
+pub const MAX_DECIMAL_EXPONENT: u32 = 4096;
+
+/// Converts a decimal literal, optionally signed or in scientific notation, into an exact rational. The integers in it
+/// are `i64` for powers and `CoeffInt` for coefficients.
+pub fn parse_number<T>(number: &str) -> Result<Ratio<T>, ParseError>
+where
+    T: Clone + Integer + Signed + CheckedMul + FromPrimitive + FromStr,
+{
+    let too_large =
+        || ParseError::Invalid(format!("{} is too large to be represented exactly", number));
+    let exponent_too_large = || {
+        ParseError::Invalid(format!(
+            "the exponent of {} is too large, the limit is {}",
+            number, MAX_DECIMAL_EXPONENT
+        ))
+    };
+    let (negative, unsigned) = match number.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, number.strip_prefix('+').unwrap_or(number)),
+    };
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(i) => (
+            &unsigned[..i],
+            unsigned[i + 1..]
+                .parse::<i32>()
+                .map_err(|_| exponent_too_large())?,
+        ),
+        None => (unsigned, 0),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    // Trailing zeros after the point do not change the value, but could overflow the digits
+    let frac_part = frac_part.trim_end_matches('0');
+    let mut digits = format!("{}{}", int_part, frac_part)
+        .parse::<T>()
+        .map_err(|_| too_large())?;
+    if digits.is_zero() {
+        return Ok(Ratio::zero());
+    }
+    let mut exponent = i32::try_from(frac_part.len())
+        .ok()
+        .and_then(|shift| exponent.checked_sub(shift))
+        .ok_or_else(exponent_too_large)?;
+    let ten = T::from_u8(10).unwrap();
+    // Drop trailing zeros first so that e.g. 1500e-20 does not need 10^20
+    while exponent < 0 && digits.is_multiple_of(&ten) {
+        digits = digits / ten.clone();
+        exponent += 1;
+    }
+    if negative {
+        digits = -digits;
+    }
+    if exponent.unsigned_abs() > MAX_DECIMAL_EXPONENT {
+        return Err(exponent_too_large());
+    }
+    let power = num::checked_pow(ten, exponent.unsigned_abs() as usize).ok_or_else(too_large)?;
+    if exponent >= 0 {
+        digits
+            .checked_mul(&power)
+            .map(Ratio::from_integer)
+            .ok_or_else(too_large)
+    } else {
+        Ok(Ratio::new(digits, power))
+    }
+}
+
+/// Parses a signed number, or a fraction of two of them like `-3/4` or `3/-4`. The sign ends up on the numerator.
+fn parse_ratio<T>(ratio: Pair<Rule>) -> Result<Ratio<T>, ParseError>
+where
+    T: Clone + Integer + Signed + CheckedMul + FromPrimitive + FromStr,
+{
+    let mut iter = ratio.into_inner();
+    let numerator = parse_number(iter.next().unwrap().as_str())?;
+    match iter.next() {
+        Some(denominator) => {
+            let denominator = parse_number(denominator.as_str())?;
+            if denominator.is_zero() {
+                return Err(ParseError::Invalid(
+                    "division by zero in fraction".to_string(),
+                ));
+            }
+            Ok(numerator / denominator)
+        }
+        None => Ok(numerator),
+    }
+}
+
+fn parse_variable(var: Pair<Rule>) -> Result<polynomial::Variable, ParseError> {
+    let mut iter = var.into_inner();
+    let name = iter.next().unwrap().as_str().to_string();
+    let degree = match iter.next() {
+        Some(d) if d.as_rule() == Rule::ratio => parse_ratio(d)?,
+        Some(d) => parse_number(d.as_str())?,
+        None => 1.into(),
+    };
+    Ok(polynomial::Variable { name, degree })
+}
+
+fn parse_term(term: Pair<Rule>) -> Result<polynomial::Term, ParseError> {
+    let mut result = polynomial::Term {
+        coefficient: Coeff::one(),
+        variables: Vec::new(),
+    };
+    for factor in term.into_inner() {
+        match factor.as_rule() {
+            Rule::sign => {
+                if factor.as_str() == "-" {
+                    result.coefficient = -result.coefficient;
+                }
+            }
+            Rule::number => {
+                result.coefficient *= parse_number(factor.as_str())?;
+            }
+            Rule::coefficient => {
+                // Nested parentheses like `((1/2))` hold a single ratio
+                let mut inner = factor.into_inner().next().unwrap();
+                while inner.as_rule() == Rule::coefficient {
+                    inner = inner.into_inner().next().unwrap();
+                }
+                result.coefficient *= parse_ratio(inner)?;
+            }
+            Rule::var => {
+                let variable = parse_variable(factor)?;
+                result.variables.push(variable);
+            }
+            Rule::EOI => (),
+            _ => unreachable!(),
+        }
+    }
+    Ok(result)
+}
+
+pub fn parse_polynomial(expression: Pairs<Rule>) -> Result<polynomial::Polynomial, ParseError> {
+    let mut p = polynomial::Polynomial {
+        terms: Vec::new(),
+        pending_pow: 1.into(),
+    };
+    for part in expression {
+        match part.as_rule() {
+            Rule::term => p.terms.push(parse_term(part)?),
+            Rule::EOI => (),
+            _ => unreachable!(),
+        }
+    }
+    Ok(p)
+}
+
+fn parse_exponent(exponent: Pair<Rule>) -> Result<i64, ParseError> {
+    let inner = exponent.into_inner().next().unwrap();
+    let q = match inner.as_rule() {
+        Rule::ratio => parse_ratio(inner)?,
+        _ => parse_number(inner.as_str())?,
+    };
+    if !q.is_integer() {
+        return Err(ParseError::Invalid(format!(
+            "only integer exponents are supported on expressions, found {}",
+            q
+        )));
+    }
+    Ok(q.to_integer())
+}
+
+fn parse_operand(operand: Pair<Rule>, scope: &impl Scope) -> Result<PolyRatio, ParseError> {
+    match operand.as_rule() {
+        Rule::ans => scope.last_result().ok_or_else(|| {
+            ParseError::Invalid("there is no previous result to use as ans".to_string())
+        }),
+        Rule::term => Ok(PolyRatio::from(polynomial::Polynomial {
+            terms: vec![parse_term(operand)?],
+            pending_pow: 1.into(),
+        })),
+        Rule::negation => {
+            let mut iter = operand.into_inner();
+            let sign = iter.next().unwrap();
+            let value = parse_operand(iter.next().unwrap(), scope)?;
+            if sign.as_str() == "-" {
+                Ok(-simplified(value)?)
+            } else {
+                Ok(value)
+            }
+        }
+        Rule::sqrt => {
+            let inner = parse_operation(operand.into_inner().next().unwrap().into_inner(), scope)?;
+            let mut inner = scope.expand(&inner)?;
+            inner.simplify();
+            Ok(match as_polynomial(&inner) {
+                Some(p) => PolyRatio::from(p.sqrt()),
+                None => PolyRatio {
+                    numerator: inner.numerator.sqrt(),
+                    denominator: inner.denominator.sqrt(),
+                },
+            })
+        }
+        Rule::call => {
+            let mut iter = operand.into_inner();
+            let name = iter.next().unwrap().as_str();
+            let mut args = Vec::new();
+            let mut exponent = None;
+            for pair in iter {
+                match pair.as_rule() {
+                    Rule::exponent => exponent = Some(parse_exponent(pair)?),
+                    _ => args.push(parse_operation(pair.into_inner(), scope)?),
+                }
+            }
+            let value = match scope.call(name, &args) {
+                Some(result) => PolyRatio::from(result?),
+                None if args.len() == 1 => {
+                    // Not a function, so this is a variable multiplied by a group like `x(y+1)`
+                    let var = polynomial::Polynomial {
+                        terms: vec![polynomial::Term {
+                            coefficient: Coeff::one(),
+                            variables: vec![polynomial::Variable {
+                                name: name.to_string(),
+                                degree: 1.into(),
+                            }],
+                        }],
+                        pending_pow: 1.into(),
+                    };
+                    let group = simplified(args.pop().unwrap())?;
+                    return Ok(PolyRatio::from(var)
+                        * match exponent {
+                            Some(exp) => group.pow(exp),
+                            None => group,
+                        });
+                }
+                None => return Err(ParseError::Invalid(format!("undefined function {}", name))),
+            };
+            match exponent {
+                Some(exp) => Ok(simplified(value)?.pow(exp)),
+                None => Ok(value),
+            }
+        }
+        Rule::group => {
+            let mut iter = operand.into_inner();
+            let base = parse_operation(iter.next().unwrap().into_inner(), scope)?;
+            match iter.next() {
+                Some(exponent) => Ok(simplified(base)?.pow(parse_exponent(exponent)?)),
+                None => Ok(base),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Multiplies and divides the operands of a product from left to right.
+/// Operands written next to each other without an operator, like `2(x+1)`, are multiplied.
+fn parse_product(product: Pairs<Rule>, scope: &impl Scope) -> Result<PolyRatio, ParseError> {
+    let mut iter = product;
+    let mut result = parse_operand(iter.next().unwrap(), scope)?;
+
+    while let Some(pair) = iter.next() {
+        result = simplified(result)?;
+        match pair.as_rule() {
+            Rule::mul => result *= simplified(parse_operand(iter.next().unwrap(), scope)?)?,
+            Rule::div => {
+                let divisor = simplified(parse_operand(iter.next().unwrap(), scope)?)?;
+                result /= divisor;
+                if result.denominator.is_zero() {
+                    return Err(ParseError::DivisionByZero { at: vec![] });
+                }
+            }
+            _ => result *= simplified(parse_operand(pair, scope)?)?,
+        }
+    }
+    Ok(result)
+}
+
+/// Checks that a value has no roots left unsimplified, since the arithmetic on polynomials would lose them.
+fn simplified(value: PolyRatio) -> Result<PolyRatio, ParseError> {
+    if value.numerator.pending_pow != 1.into() || value.denominator.pending_pow != 1.into() {
+        return Err(ParseError::Invalid(format!(
+            "{} does not simplify and cannot be combined with other expressions",
+            value.as_string()?
+        )));
+    }
+    Ok(value)
+}
+
+/// Adds and subtracts the products of an operation from left to right. Each product is parsed whole first, so `*` and
+/// `/` bind tighter than `+` and `-`, and an operand can start with any number of signs, also before a group.
+///
+/// ```
+/// use pest::Parser;
+/// use sym_tfg::parse::{parse_operation, EmptyScope, PolyParser, Rule};
+///
+/// let parse = |input: &str| {
+///     let line = PolyParser::parse(Rule::input_operation, input).unwrap().next().unwrap();
+///     let operation = line.into_inner().next().unwrap();
+///     parse_operation(operation.into_inner(), &EmptyScope).unwrap().as_string().unwrap()
+/// };
+/// // x - (2x)/4 + 1, not ((x - 2) x / 4) + 1
+/// assert_eq!(parse("x - 2 * x / 4 + 1"), "(x+2) / (2)");
+/// assert_eq!(parse("1 + 2 * x"), "2x+1");
+/// assert_eq!(parse("--x"), "x");
+/// assert_eq!(parse("- (x+1) * 2"), "-2x-2");
+/// ```
+pub fn parse_operation(
+    operation: Pairs<Rule>,
+    scope: &impl Scope,
+) -> Result<PolyRatio, ParseError> {
+    let mut iter = operation;
+    let mut result = parse_product(iter.next().unwrap().into_inner(), scope)?;
+
+    while let Some(op) = iter.next() {
+        result = simplified(result)?;
+        let next = simplified(parse_product(iter.next().unwrap().into_inner(), scope)?)?;
+        match op.as_rule() {
+            Rule::add => result += next,
+            Rule::sub => result -= next,
+            _ => unreachable!(),
+        }
+    }
+    Ok(result)
+}
+
+/// Returns everything the operation divides by as written, before `simplify` cancels anything: the right side of every
+/// `/`, the base of every group with a negative exponent, and the variables with negative powers. The expression is
+/// undefined wherever one of them is zero or undefined, even when the factor cancels out of its value.
+pub fn divisors(operation: Pairs<Rule>, scope: &impl Scope) -> Result<Vec<PolyRatio>, ParseError> {
+    let mut result = Vec::new();
+    for pair in operation {
+        collect_divisors(pair, scope, &mut result)?;
+    }
+    Ok(result)
+}
+
+fn collect_divisors(
+    pair: Pair<Rule>,
+    scope: &impl Scope,
+    divisors: &mut Vec<PolyRatio>,
+) -> Result<(), ParseError> {
+    match pair.as_rule() {
+        Rule::product => {
+            let mut iter = pair.into_inner();
+            while let Some(pair) = iter.next() {
+                if pair.as_rule() == Rule::div {
+                    let operand = iter.next().unwrap();
+                    divisors.push(parse_operand(operand.clone(), scope)?);
+                    collect_divisors(operand, scope, divisors)?;
+                } else {
+                    collect_divisors(pair, scope, divisors)?;
+                }
+            }
+        }
+        Rule::group => {
+            let mut iter = pair.into_inner();
+            let operation = iter.next().unwrap();
+            if let Some(exponent) = iter.next() {
+                if parse_exponent(exponent)? < 0 {
+                    divisors.push(parse_operation(operation.clone().into_inner(), scope)?);
+                }
+            }
+            collect_divisors(operation, scope, divisors)?;
+        }
+        Rule::term => {
+            for var in parse_term(pair)?.variables {
+                if var.degree < 0.into() {
+                    let power = [(var.name.as_str(), -var.degree)];
+                    divisors.push(PolyRatio::from(polynomial::Polynomial::monomial(
+                        Coeff::one(),
+                        &power,
+                    )));
+                }
+            }
+        }
+        _ => {
+            for inner in pair.into_inner() {
+                collect_divisors(inner, scope, divisors)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the ratio as a single expanded polynomial, or `None` if its denominator is not a single nonzero term
+/// or it has a root that does not simplify. Variables in the denominator become negative powers.
+pub fn as_polynomial(ratio: &PolyRatio) -> Option<polynomial::Polynomial> {
+    let mut ratio = simplified(ratio.clone()).ok()?;
+    ratio.simplify();
+    match ratio.denominator.terms.as_slice() {
+        [d] if !d.coefficient.is_zero() => {
+            let mut inverse = d.clone();
+            inverse.invert();
+            Some(
+                ratio.numerator
+                    * polynomial::Polynomial {
+                        terms: vec![inverse],
+                        pending_pow: 1.into(),
+                    },
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Gives expressions access to the state of the interpreter: the previous result, user functions and bound variables.
+/// The default methods describe an empty scope.
+pub trait Scope {
+    /// The value of `ans`.
+    fn last_result(&self) -> Option<PolyRatio> {
+        None
+    }
+
+    /// Calls the function `name` with the arguments, or returns `None` if there is no function with that name.
+    fn call(
+        &self,
+        _name: &str,
+        _args: &[PolyRatio],
+    ) -> Option<Result<polynomial::Polynomial, ParseError>> {
+        None
+    }
+
+    /// Replaces the variables bound to values, which is needed before taking a square root.
+    fn expand(&self, ratio: &PolyRatio) -> Result<PolyRatio, ParseError> {
+        Ok(ratio.clone())
+    }
+}
+
+/// A scope without a previous result, functions or bound variables, used to parse standalone expressions.
+pub struct EmptyScope;
+
+impl Scope for EmptyScope {}
+
+/// The error returned when parsing an expression or running a statement of the interpreter fails.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The input does not follow the grammar.
+    Syntax(Box<pest::error::Error<Rule>>),
+    /// The input follows the grammar but divides by something that simplifies to zero, like `1/(x-x)`, or that is zero
+    /// at the values in `at`, which were bound to the variables.
+    DivisionByZero { at: Vec<(String, Coeff)> },
+    /// An operation on the polynomials failed, like a coefficient that overflowed.
+    Poly(PolyError),
+    /// The input follows the grammar but has no other valid value, like a polynomial with a denominator.
+    Invalid(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax(e) => write!(f, "{}", e),
+            ParseError::DivisionByZero { at } => {
+                write!(f, "{}", PolyError::DivisionByZero { at: at.clone() })
+            }
+            ParseError::Poly(e) => write!(f, "{}", e),
+            ParseError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<pest::error::Error<Rule>> for ParseError {
+    fn from(e: pest::error::Error<Rule>) -> Self {
+        ParseError::Syntax(Box::new(e))
+    }
+}
+
+impl From<PolyError> for ParseError {
+    fn from(e: PolyError) -> Self {
+        match e {
+            PolyError::DivisionByZero { at } => ParseError::DivisionByZero { at },
+            e => ParseError::Poly(e),
+        }
+    }
+}
+
+/// Parses a whole string as an operation in an empty scope.
+fn parse_standalone(input: &str) -> Result<PolyRatio, ParseError> {
+    let operation = PolyParser::parse(Rule::input_operation, input)?
+        .next()
+        .unwrap()
+        .into_inner()
+        .next()
+        .unwrap();
+    parse_operation(operation.into_inner(), &EmptyScope)
+}
+
+/// Parses an expression written like the input of the interpreter, which must simplify to a polynomial.
+/// Variables in a denominator become negative powers.
+///
+/// ```
+/// use sym_tfg::polynomial::Polynomial;
+///
+/// let p: Polynomial = "3*x^2 - 1/2".parse().unwrap();
+/// assert_eq!(p.as_string(), "3x^(2)-1/2");
+/// ```
+impl FromStr for polynomial::Polynomial {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ratio = parse_standalone(s)?;
+        match as_polynomial(&ratio) {
+            Some(p) => Ok(p),
+            None => Err(ParseError::Invalid(format!(
+                "expected a polynomial, found {}",
+                ratio.as_string()?
+            ))),
+        }
+    }
+}
+
+/// Parses an expression written like the input of the interpreter, like `(x+1)/(x-1)`.
+///
+/// ```
+/// use sym_tfg::parse::ParseError;
+/// use sym_tfg::polynomial::PolyRatio;
+///
+/// let r: PolyRatio = "(x^2 - 1)/(x + 1)".parse().unwrap();
+/// assert_eq!(r.as_string().unwrap(), "x-1");
+/// assert!(matches!("1/(x - x)".parse::<PolyRatio>(), Err(ParseError::DivisionByZero { .. })));
+/// assert!(matches!("1/(x - ".parse::<PolyRatio>(), Err(ParseError::Syntax(_))));
+/// ```
+impl FromStr for PolyRatio {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_standalone(s)
+    }
+}
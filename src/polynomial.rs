@@ -1,8 +1,718 @@
-use core::panic;
-use num::rational::Rational64;
-use num::{FromPrimitive, ToPrimitive};
+// Coefficients are not `Copy` with the `bigrational` feature, so they are cloned and borrowed even where 64-bit ones
+// would not need it
+#![cfg_attr(
+    not(feature = "bigrational"),
+    allow(clippy::clone_on_copy, clippy::op_ref)
+)]
+
+use num::integer::Roots;
+use num::pow::Pow;
+use num::rational::{Ratio, Rational64};
+#[cfg(feature = "bigrational")]
+use num::{BigInt, BigRational};
+use num::{
+    CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, FromPrimitive, Integer, One, Signed,
+    ToPrimitive, Zero,
+};
+use std::any::Any;
 use std::cmp::Ordering;
-use std::ops::{Add, Div, Mul, Sub};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+
+/// Errors produced by polynomial operations that have no valid result.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolyError {
+    /// The variable appears with a fractional exponent where only integer exponents are supported.
+    FractionalExponent { var: String, degree: Rational64 },
+    /// The result would have a polynomial (or zero) in a denominator.
+    NotPolynomial { var: String, degree: Rational64 },
+    /// There is no method to find the roots of a polynomial of this degree.
+    UnsupportedDegree { var: String, degree: Rational64 },
+    /// The integral of the variable to the power of -1 is a logarithm, not a polynomial.
+    LogarithmicIntegral { var: String },
+    /// The variable needs a value but was not given one.
+    UnboundVariable { var: String },
+    /// The variable cannot be given this value exactly, like 2^(1/2) or 0^(-1).
+    NoExactValue {
+        var: String,
+        degree: Rational64,
+        value: Coeff,
+    },
+    /// The variable has a negative value and a power that is an even root, like x^(1/2) at x = -4, so it is not real.
+    NotReal {
+        var: String,
+        degree: Rational64,
+        value: Coeff,
+    },
+    /// The variable appears with a negative exponent where only non-negative exponents are supported.
+    NegativeExponent { var: String, degree: Rational64 },
+    /// The polynomial depends on another variable besides the expected one.
+    NotUnivariate { var: String, other: String },
+    /// The result could have more terms than allowed.
+    TooManyTerms { terms: u128, limit: usize },
+    /// Two points to interpolate have the same x value but different y values.
+    ConflictingPoints { x: Coeff, y1: Coeff, y2: Coeff },
+    /// The denominator of a ratio is zero, at the values given to the variables in `at` if there are any.
+    DivisionByZero { at: Vec<(String, Coeff)> },
+    /// Every value of the variable is a root of the zero polynomial, so they cannot be listed.
+    ZeroPolynomial { var: String },
+    /// A fraction given to `PolynomialBuilder`, as a coefficient or a power, has a zero denominator.
+    ZeroDenominator,
+    /// Arithmetic modulo a number that is not prime, so the coefficients would not form a field.
+    NotPrime { modulus: i64 },
+    /// The integrand has a negative power of the variable and the interval contains 0, where it is not bounded, so the
+    /// integral is improper and is not computed.
+    ImproperIntegral { var: String, degree: Rational64 },
+    /// A coefficient has a denominator that is a multiple of the modulus, so it has no value modulo it.
+    NoInverseMod { denominator: CoeffInt, modulus: i64 },
+    /// A coefficient or an exponent does not fit in 64 bits; for coefficients, only possible without the `bigrational`
+    /// feature. The operators, which cannot return it, stop with it as their panic payload instead, see `catch_overflow`.
+    CoefficientOverflow,
+}
+
+impl fmt::Display for PolyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolyError::FractionalExponent { var, degree } => write!(
+                f,
+                "{}^({}) has a fractional exponent, only integer exponents are supported",
+                var, degree
+            ),
+            PolyError::NotPolynomial { var, degree } => write!(
+                f,
+                "substituting into {}^({}) does not give a polynomial",
+                var, degree
+            ),
+            PolyError::UnsupportedDegree { var, degree } => write!(
+                f,
+                "equations of degree {} in {} cannot be solved",
+                degree, var
+            ),
+            PolyError::LogarithmicIntegral { var } => write!(
+                f,
+                "the integral of {}^(-1) is a logarithm, not a polynomial",
+                var
+            ),
+            PolyError::UnboundVariable { var } => write!(f, "{} has no value", var),
+            PolyError::NoExactValue { var, degree, value } => write!(
+                f,
+                "{}^({}) has no exact rational value at {} = {}",
+                var, degree, var, value
+            ),
+            PolyError::NotReal { var, degree, value } => write!(
+                f,
+                "{}^({}) is not a real number at {} = {}",
+                var, degree, var, value
+            ),
+            PolyError::NegativeExponent { var, degree } => write!(
+                f,
+                "{}^({}) has a negative exponent, only non-negative exponents are supported",
+                var, degree
+            ),
+            PolyError::NotUnivariate { var, other } => write!(
+                f,
+                "expected a polynomial in {} only, but it also depends on {}",
+                var, other
+            ),
+            PolyError::TooManyTerms { terms, limit } => write!(
+                f,
+                "the result could have up to {} terms, more than the limit of {}",
+                terms, limit
+            ),
+            PolyError::ConflictingPoints { x, y1, y2 } => write!(
+                f,
+                "no function goes through both ({}, {}) and ({}, {})",
+                x, y1, x, y2
+            ),
+            PolyError::DivisionByZero { at } if at.is_empty() => write!(f, "division by zero"),
+            PolyError::DivisionByZero { at } => {
+                let values: Vec<String> = at
+                    .iter()
+                    .map(|(var, value)| format!("{} = {}", var, value))
+                    .collect();
+                write!(f, "division by zero at {}", values.join(", "))
+            }
+            PolyError::ZeroPolynomial { var } => {
+                write!(f, "every value of {} is a root of the zero polynomial", var)
+            }
+            PolyError::ZeroDenominator => write!(f, "a fraction has a zero denominator"),
+            PolyError::NotPrime { modulus } => write!(
+                f,
+                "{} is not a prime number, arithmetic modulo it is not supported",
+                modulus
+            ),
+            PolyError::ImproperIntegral { var, degree } => write!(
+                f,
+                "{}^({}) is not bounded at {} = 0, which is in the interval of integration",
+                var, degree, var
+            ),
+            PolyError::NoInverseMod {
+                denominator,
+                modulus,
+            } => write!(
+                f,
+                "a coefficient has the denominator {}, which has no inverse modulo {}",
+                denominator, modulus
+            ),
+            PolyError::CoefficientOverflow => {
+                write!(f, "a number is too large to fit in 64 bits")
+            }
+        }
+    }
+}
+
+/// Stops an operator whose coefficients overflowed. Operators like `*` cannot return an error, so they panic with
+/// `PolyError::CoefficientOverflow` as the payload, which `catch_overflow` turns back into the error.
+fn overflow() -> ! {
+    std::panic::panic_any(PolyError::CoefficientOverflow)
+}
+
+/// Runs `f`, returning `PolyError::CoefficientOverflow` if an operator on polynomials inside it overflowed. Any other panic is resumed.
+/// The value of whatever `f` was changing when it overflowed is unspecified, so work on copies that can be thrown away.
+pub fn catch_overflow<R>(f: impl FnOnce() -> R) -> Result<R, PolyError> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(result) => Ok(result),
+        Err(payload) if is_overflow(payload.as_ref()) => Err(PolyError::CoefficientOverflow),
+        Err(payload) => std::panic::resume_unwind(payload),
+    }
+}
+
+/// True if the panic payload is the one of an operator whose coefficients overflowed, so a panic hook can leave it
+/// to `catch_overflow` instead of printing it.
+pub fn is_overflow(payload: &(dyn Any + Send)) -> bool {
+    payload.downcast_ref::<PolyError>() == Some(&PolyError::CoefficientOverflow)
+}
+
+/// Evaluates the terms as a polynomial in the first of `names`, whose coefficients are evaluated the same way in the rest of `names`.
+fn horner(terms: &[&Term], names: &[&str], values: &[(String, Coeff)]) -> Result<Coeff, PolyError> {
+    let (name, rest) = match names.split_first() {
+        Some(split) => split,
+        None => return Ok(terms.iter().map(|t| t.coefficient.clone()).sum()),
+    };
+    let value = values.iter().find(|(n, _)| n == name).unwrap().1.clone();
+
+    // Group the terms by their power of the variable, from the highest to the lowest
+    let mut groups: Vec<(i64, Vec<&Term>)> = Vec::new();
+    for term in terms {
+        let degree: Rational64 = term
+            .variables
+            .iter()
+            .filter(|v| v.name == *name)
+            .map(|v| v.degree)
+            .sum();
+        if !degree.is_integer() {
+            return Err(PolyError::FractionalExponent {
+                var: name.to_string(),
+                degree,
+            });
+        }
+        match groups.iter_mut().find(|(d, _)| *d == degree.to_integer()) {
+            Some((_, group)) => group.push(term),
+            None => groups.push((degree.to_integer(), vec![term])),
+        }
+    }
+    groups.sort_by_key(|(d, _)| std::cmp::Reverse(*d));
+
+    let lowest = groups.last().map(|(d, _)| *d).unwrap_or(0);
+    if value.is_zero() && lowest < 0 {
+        return Err(PolyError::NoExactValue {
+            var: name.to_string(),
+            degree: lowest.into(),
+            value,
+        });
+    }
+    let mut result = Coeff::zero();
+    let mut previous = groups.first().map(|(d, _)| *d).unwrap_or(0);
+    for (degree, group) in &groups {
+        let inner = horner(group, rest, values)?;
+        result = exact_pow(value.clone(), (previous - degree).into())
+            .and_then(|power| result.checked_mul(&power)?.checked_add(&inner))
+            .ok_or(PolyError::CoefficientOverflow)?;
+        previous = *degree;
+    }
+    exact_pow(value.clone(), lowest.into())
+        .and_then(|power| result.checked_mul(&power))
+        .ok_or(PolyError::CoefficientOverflow)
+}
+
+/// Raises the value of the variable `var` to its power `degree` exactly, or says why that is not possible: the power
+/// overflows, is an even root of a negative value, or is not rational.
+fn exact_power(var: &str, degree: Rational64, value: &Coeff) -> Result<Coeff, PolyError> {
+    if let Some(power) = exact_pow(value.clone(), degree) {
+        return Ok(power);
+    }
+    if degree.is_integer() && !value.is_zero() {
+        // A whole power of a nonzero value is always rational, so it can only have overflowed
+        Err(PolyError::CoefficientOverflow)
+    } else if value.is_negative() && degree.denom().is_even() {
+        Err(PolyError::NotReal {
+            var: var.to_string(),
+            degree,
+            value: value.clone(),
+        })
+    } else {
+        Err(PolyError::NoExactValue {
+            var: var.to_string(),
+            degree,
+            value: value.clone(),
+        })
+    }
+}
+
+/// Raises a float to a rational power, avoiding `powf` for integer exponents.
+fn powf64(base: f64, exp: Rational64) -> Option<f64> {
+    if exp.is_integer() {
+        Some(base.powi(i32::try_from(exp.to_integer()).ok()?))
+    } else {
+        Some(base.powf(exp.to_f64()?))
+    }
+}
+
+/// Raises a rational number to a rational power exactly.
+/// Returns `None` if the result is not rational, is undefined, or does not fit in a `Coeff`.
+pub fn exact_pow(base: Coeff, exp: Rational64) -> Option<Coeff> {
+    if base.is_zero() {
+        return match exp.cmp(&0.into()) {
+            Ordering::Greater => Some(Coeff::zero()),
+            Ordering::Equal => Some(Coeff::one()),
+            Ordering::Less => None,
+        };
+    }
+    let root = u32::try_from(*exp.denom()).ok()?;
+    let power = usize::try_from(exp.numer().unsigned_abs()).ok()?;
+    let negative = base.is_negative();
+    if negative && root % 2 == 0 {
+        return None;
+    }
+    let numer = exact_root(&base.numer().abs(), root)?;
+    let denom = exact_root(base.denom(), root)?;
+    let mut numer = num::checked_pow(numer, power)?;
+    let denom = num::checked_pow(denom, power)?;
+    if negative && power % 2 == 1 {
+        numer = -numer;
+    }
+    if exp < 0.into() {
+        Some(Coeff::new(denom, numer))
+    } else {
+        Some(Coeff::new(numer, denom))
+    }
+}
+
+/// Returns the `n`-th root of `x`, which is not negative, if it is a whole number.
+fn exact_root(x: &CoeffInt, n: u32) -> Option<CoeffInt> {
+    let root = Roots::nth_root(x, n);
+    (num::checked_pow(root.clone(), n as usize).as_ref() == Some(x)).then_some(root)
+}
+
+/// Returns the determinant of a square matrix of fractions with fraction-free (Bareiss) elimination.
+/// Every division in it is exact, so the entries stay as small as the minors of the matrix.
+pub fn determinant(matrix: &[Vec<Coeff>]) -> Coeff {
+    let mut m = matrix.to_vec();
+    let k = m.len();
+    let mut sign = Coeff::one();
+    let mut previous = Coeff::one();
+    for i in 0..k.saturating_sub(1) {
+        if m[i][i].is_zero() {
+            match (i + 1..k).find(|&r| !m[r][i].is_zero()) {
+                Some(r) => {
+                    m.swap(i, r);
+                    sign = -sign;
+                }
+                None => return Coeff::zero(),
+            }
+        }
+        for j in i + 1..k {
+            for l in i + 1..k {
+                m[j][l] = (&m[j][l] * &m[i][i] - &m[j][i] * &m[i][l]) / &previous;
+            }
+        }
+        previous = m[i][i].clone();
+    }
+    match m.last() {
+        Some(row) => sign * &row[k - 1],
+        None => Coeff::one(),
+    }
+}
+
+/// Returns the determinant of a square matrix of polynomials like `determinant`. The divisions are exact polynomial
+/// divisions, so it works with entries in several variables.
+fn polynomial_determinant(mut m: Vec<Vec<Polynomial>>) -> Polynomial {
+    let k = m.len();
+    let mut sign = Polynomial::one();
+    let mut previous = Polynomial::one();
+    for i in 0..k.saturating_sub(1) {
+        if m[i][i].is_zero() {
+            match (i + 1..k).find(|&r| !m[r][i].is_zero()) {
+                Some(r) => {
+                    m.swap(i, r);
+                    sign = -sign;
+                }
+                None => return Polynomial::zero(),
+            }
+        }
+        for j in i + 1..k {
+            for l in i + 1..k {
+                let cross = m[j][l].clone() * m[i][i].clone() - m[j][i].clone() * m[i][l].clone();
+                m[j][l] = cross
+                    .reduce(&[previous.clone()], MonomialOrder::default())
+                    .0
+                    .remove(0);
+            }
+        }
+        previous = m[i][i].clone();
+    }
+    match m.last() {
+        Some(row) => sign * row[k - 1].clone(),
+        None => Polynomial::one(),
+    }
+}
+
+/// The default bound on the candidate factors `Polynomial::factor_kronecker` tries for each degree.
+pub const KRONECKER_CANDIDATES: usize = 100_000;
+
+/// Splits a primitive polynomial in `var` with no rational roots into irreducible factors with Kronecker's method. A factor
+/// of degree d is determined by its values at d+1 integers, which must divide the values of `p` there, so every choice of
+/// divisors is interpolated and tried as a divisor of `p`. Each factor has a positive leading coefficient.
+fn kronecker_split(p: &Polynomial, var: &str, max_candidates: usize) -> Vec<Polynomial> {
+    let coeffs = match p.to_coeff_vec(var) {
+        Ok(coeffs) => coeffs,
+        Err(_) => return vec![p.clone()],
+    };
+    let n = coeffs.len().saturating_sub(1);
+    let value_at = |a: i64| {
+        coeffs.iter().rev().try_fold(Coeff::zero(), |acc, c| {
+            acc.checked_mul(&coeff(a))?.checked_add(c)
+        })
+    };
+    // Integers where p is not zero, in the order 0, 1, -1, 2, -2, ...
+    let mut nodes: Vec<(i64, i64)> = Vec::new();
+    let mut a = 0;
+    while nodes.len() <= n / 2 {
+        // Values too large for the divisors to be listed are not searched
+        let value = match value_at(a) {
+            Some(value) => value,
+            None => return vec![p.clone()],
+        };
+        if !value.is_zero() {
+            match value.to_integer().to_i64() {
+                Some(value) => nodes.push((a, value)),
+                None => return vec![p.clone()],
+            }
+        }
+        a = if a > 0 { -a } else { -a + 1 };
+    }
+    for d in 1..=n / 2 {
+        let nodes = &nodes[..=d];
+        let choices: Vec<Vec<i64>> = nodes
+            .iter()
+            .map(|(_, value)| divisors(*value).into_iter().flat_map(|e| [e, -e]).collect())
+            .collect();
+        let count = choices
+            .iter()
+            .try_fold(1usize, |acc, c| acc.checked_mul(c.len()));
+        if count.is_none_or(|count| count > max_candidates) {
+            return vec![p.clone()];
+        }
+        // Go through every choice of divisors like the digits of a counter
+        let mut index = vec![0; d + 1];
+        loop {
+            let points: Vec<(Coeff, Coeff)> = nodes
+                .iter()
+                .zip(&index)
+                .zip(&choices)
+                .map(|(((a, _), &i), c)| (coeff(*a), coeff(c[i])))
+                .collect();
+            // A candidate whose coefficients do not fit in a `Coeff` is skipped
+            let candidate = interpolation_coeffs(&points).unwrap_or_default();
+            if candidate.len() == d + 1
+                && candidate[d].is_positive()
+                && candidate.iter().all(|c| c.is_integer())
+            {
+                // A multiple of a factor divides too, but only its primitive part keeps the quotient integer
+                let (_, factor) = Polynomial::from_coeff_vec(var, &candidate).primitive_part();
+                let (quotient, remainder) = p.div_rem(&factor);
+                if remainder.is_zero() {
+                    let mut result = kronecker_split(&factor, var, max_candidates);
+                    result.extend(kronecker_split(&quotient, var, max_candidates));
+                    return result;
+                }
+            }
+            match (0..=d).find(|&k| index[k] + 1 < choices[k].len()) {
+                Some(k) => {
+                    index[k] += 1;
+                    index[..k].iter_mut().for_each(|i| *i = 0);
+                }
+                None => break,
+            }
+        }
+    }
+    vec![p.clone()]
+}
+
+/// Returns the positive divisors of `n` in increasing order.
+fn divisors(n: i64) -> Vec<i64> {
+    let n = n.unsigned_abs();
+    let mut small: Vec<i64> = Vec::new();
+    let mut large: Vec<i64> = Vec::new();
+    let mut d = 1;
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            small.push(d as i64);
+            if d * d != n {
+                large.push((n / d) as i64);
+            }
+        }
+        d += 1;
+    }
+    small.extend(large.into_iter().rev());
+    small
+}
+
+/// True if `n` is a prime number, checked by trial division.
+fn is_prime(n: i64) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| *d <= n / d)
+            .all(|d| !n.is_multiple_of(&d))
+}
+
+/// Returns the inverse of `a` modulo the prime `p`, which must not divide `a`.
+fn inverse_mod(a: &CoeffInt, p: &CoeffInt) -> CoeffInt {
+    a.extended_gcd(p).x.mod_floor(p)
+}
+
+/// Returns the remainder of dividing `a` by `b` over GF(p). Both are coefficient vectors from degree 0 upward
+/// without trailing zeros, with `b` not empty, and so is the result.
+fn rem_mod(mut a: Vec<CoeffInt>, b: &[CoeffInt], p: &CoeffInt) -> Vec<CoeffInt> {
+    let inverse = inverse_mod(b.last().unwrap(), p);
+    while a.len() >= b.len() {
+        let shift = a.len() - b.len();
+        let factor = (a.last().unwrap().clone() * inverse.clone()).mod_floor(p);
+        for (i, c) in b.iter().enumerate() {
+            a[shift + i] = (a[shift + i].clone() - factor.clone() * c.clone()).mod_floor(p);
+        }
+        while a.last().is_some_and(|c| c.is_zero()) {
+            a.pop();
+        }
+    }
+    a
+}
+
+/// Returns the coefficients, from degree 0 upward, of the polynomial of lowest degree through the points, which must have
+/// different x values. It uses Newton's divided differences.
+fn interpolation_coeffs(points: &[(Coeff, Coeff)]) -> Result<Vec<Coeff>, PolyError> {
+    let mut differences: Vec<Coeff> = points.iter().map(|(_, y)| y.clone()).collect();
+    for level in 1..points.len() {
+        for i in (level..points.len()).rev() {
+            let dy = differences[i].checked_sub(&differences[i - 1]);
+            let dx = points[i].0.checked_sub(&points[i - level].0);
+            differences[i] = dy
+                .zip(dx)
+                .and_then(|(dy, dx)| dy.checked_div(&dx))
+                .ok_or(PolyError::CoefficientOverflow)?;
+        }
+    }
+    // Horner's rule on the Newton form c0 + (x-x0)(c1 + (x-x1)(c2 + ...))
+    let mut coeffs: Vec<Coeff> = Vec::new();
+    for i in (0..points.len()).rev() {
+        // coeffs = coeffs * (x - x_i) + c_i
+        let mut next = vec![Coeff::zero(); coeffs.len() + 1];
+        for (k, c) in coeffs.iter().enumerate() {
+            next[k + 1] = next[k + 1]
+                .checked_add(c)
+                .ok_or(PolyError::CoefficientOverflow)?;
+            next[k] = points[i]
+                .0
+                .checked_mul(c)
+                .and_then(|product| next[k].checked_sub(&product))
+                .ok_or(PolyError::CoefficientOverflow)?;
+        }
+        next[0] = next[0]
+            .checked_add(&differences[i])
+            .ok_or(PolyError::CoefficientOverflow)?;
+        coeffs = next;
+    }
+    while coeffs.last().is_some_and(|c| c.is_zero()) {
+        coeffs.pop();
+    }
+    Ok(coeffs)
+}
+
+/// An order for the terms of a polynomial, used to sort and print them. Powers are compared variable by variable,
+/// taking the variables in alphabetical order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MonomialOrder {
+    /// By the power of the first variable, then of the second and so on: x^2 > xy^3 > x > y^5.
+    Lex,
+    /// By total degree, then like `Lex`: xy^3 > x^2y > y^3 > x^2. This is the default order.
+    #[default]
+    GradedLex,
+    /// By total degree, then the term with the smaller power of the last variable goes first: x^2y > xy^2 > y^3.
+    GradedRevLex,
+}
+
+impl MonomialOrder {
+    /// Compares two terms, with the term that goes first in a sorted polynomial being the lesser.
+    pub fn compare<T: Coefficient>(self, a: &Term<T>, b: &Term<T>) -> Ordering {
+        let names: BTreeSet<&str> = a
+            .variables
+            .iter()
+            .chain(&b.variables)
+            .map(|v| v.name.as_str())
+            .collect();
+        match self {
+            MonomialOrder::Lex => names
+                .iter()
+                .map(|name| b.degree_of(name).cmp(&a.degree_of(name)))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal),
+            MonomialOrder::GradedLex => b
+                .total_degree()
+                .cmp(&a.total_degree())
+                .then_with(|| MonomialOrder::Lex.compare(a, b)),
+            MonomialOrder::GradedRevLex => {
+                b.total_degree().cmp(&a.total_degree()).then_with(|| {
+                    names
+                        .iter()
+                        .rev()
+                        .map(|name| a.degree_of(name).cmp(&b.degree_of(name)))
+                        .find(|o| o.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                })
+            }
+        }
+    }
+}
+
+impl std::error::Error for PolyError {}
+
+/// The fractions used for coefficients and values. They are 64-bit by default and arbitrary precision with the
+/// `bigrational` feature, which is slower but cannot overflow.
+#[cfg(not(feature = "bigrational"))]
+pub type Coeff = Rational64;
+#[cfg(feature = "bigrational")]
+pub type Coeff = BigRational;
+
+/// The integers in the numerator and denominator of a `Coeff`.
+#[cfg(not(feature = "bigrational"))]
+pub type CoeffInt = i64;
+#[cfg(feature = "bigrational")]
+pub type CoeffInt = BigInt;
+
+/// Returns the whole number `n` as a `Coeff`.
+pub fn coeff(n: i64) -> Coeff {
+    Coeff::from_i64(n).unwrap()
+}
+
+/// Returns the power `q` as a `Coeff`, for coefficients that come from powers like the ones in derivatives.
+pub fn degree_coeff(q: Rational64) -> Coeff {
+    coeff(*q.numer()) / coeff(*q.denom())
+}
+
+/// The numbers a polynomial can have as coefficients. `Coeff` is the default and the only one the parser,
+/// the division and everything built on it work with; other types like `f64` get the ring arithmetic,
+/// `simplify`, `as_string` and, through `RatioArithmetic`, the operators on ratios.
+pub trait Coefficient: Signed + Clone + PartialOrd + fmt::Display + CheckedArithmetic {}
+
+impl<T: Signed + Clone + PartialOrd + fmt::Display + CheckedArithmetic> Coefficient for T {}
+
+/// The arithmetic the operators on polynomials do on coefficients, returning `None` where the result does not fit
+/// instead of panicking or wrapping around. Floating point numbers do not overflow that way, so theirs never fail.
+pub trait CheckedArithmetic: Sized {
+    fn try_add(&self, other: &Self) -> Option<Self>;
+    fn try_mul(&self, other: &Self) -> Option<Self>;
+    fn try_neg(&self) -> Option<Self>;
+}
+
+impl<T: Clone + Integer + CheckedAdd + CheckedMul + CheckedSub> CheckedArithmetic for Ratio<T> {
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        CheckedAdd::checked_add(self, other)
+    }
+
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        CheckedMul::checked_mul(self, other)
+    }
+
+    fn try_neg(&self) -> Option<Self> {
+        let numer = T::zero().checked_sub(self.numer())?;
+        Some(Ratio::new_raw(numer, self.denom().clone()))
+    }
+}
+
+macro_rules! float_checked_arithmetic {
+    ($($t:ty),*) => {$(
+        impl CheckedArithmetic for $t {
+            fn try_add(&self, other: &Self) -> Option<Self> {
+                Some(self + other)
+            }
+
+            fn try_mul(&self, other: &Self) -> Option<Self> {
+                Some(self * other)
+            }
+
+            fn try_neg(&self) -> Option<Self> {
+                Some(-self)
+            }
+        }
+    )*};
+}
+
+float_checked_arithmetic!(f32, f64);
+
+/// How the operators on ratios bring two ratios over a common denominator and cancel the factors a result has in
+/// common. `Coeff` finds the least common multiple and the greatest common divisor exactly; floating point numbers,
+/// which cannot divide polynomials exactly, multiply the denominators unless they are the same and cancel nothing.
+///
+/// ```
+/// use sym_tfg::polynomial::{PolyRatio, Polynomial, Term, Variable};
+///
+/// let x = Variable { name: "x".to_string(), degree: 1.into() };
+/// let term = |coefficient: f64, variables: &[Variable]| Term { coefficient, variables: variables.to_vec() };
+/// let poly = |terms| Polynomial { terms, pending_pow: 1.into() };
+/// let one = poly(vec![term(1.0, &[])]);
+/// let a = PolyRatio { numerator: one.clone(), denominator: poly(vec![term(1.0, &[x.clone()])]) };
+/// let b = PolyRatio { numerator: one, denominator: poly(vec![term(1.0, &[x]), term(1.0, &[])]) };
+/// assert_eq!((a.clone() + a.clone()).as_string().unwrap(), "(2) / (x)");
+/// assert_eq!((a.clone() + b.clone()).as_string().unwrap(), "(2x+1) / (x^(2)+x)");
+/// assert_eq!((a.clone() - b.clone()).as_string().unwrap(), "(1) / (x^(2)+x)");
+/// assert_eq!((a.clone() * b.clone()).as_string().unwrap(), "(1) / (x^(2)+x)");
+/// assert_eq!((a / b).as_string().unwrap(), "(x+1) / (x)");
+/// ```
+pub trait RatioArithmetic: Coefficient {
+    /// Returns the common denominator of `a` and `b`, and the polynomials their denominators are multiplied by to reach it.
+    fn common_denominator(
+        a: &PolyRatio<Self>,
+        b: &PolyRatio<Self>,
+    ) -> (Polynomial<Self>, Polynomial<Self>, Polynomial<Self>);
+    fn simplify_ratio(ratio: &mut PolyRatio<Self>);
+}
+
+macro_rules! float_ratio_arithmetic {
+    ($($t:ty),*) => {$(
+        impl RatioArithmetic for $t {
+            fn common_denominator(
+                a: &PolyRatio<Self>,
+                b: &PolyRatio<Self>,
+            ) -> (Polynomial<Self>, Polynomial<Self>, Polynomial<Self>) {
+                let (d1, d2) = (&a.denominator, &b.denominator);
+                if d1 == d2 {
+                    (d1.clone(), Polynomial::one(), Polynomial::one())
+                } else {
+                    (d1.clone() * d2.clone(), d2.clone(), d1.clone())
+                }
+            }
+
+            fn simplify_ratio(ratio: &mut PolyRatio<Self>) {
+                ratio.numerator.simplify();
+                ratio.denominator.simplify();
+            }
+        }
+    )*};
+}
+
+float_ratio_arithmetic!(f32, f64);
 
 #[derive(Debug, Clone, Eq, PartialOrd, Ord)]
 pub struct Variable {
@@ -16,13 +726,20 @@ impl PartialEq for Variable {
     }
 }
 
+impl Hash for Variable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.degree.hash(state);
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct Term {
-    pub coefficient: Rational64,
+pub struct Term<T = Coeff> {
+    pub coefficient: T,
     pub variables: Vec<Variable>,
 }
 
-impl Term {
+impl<T: Coefficient> Term<T> {
     /// Find max degree of the variables in the term.
     pub fn max_degree(&self) -> Rational64 {
         self.variables
@@ -32,6 +749,27 @@ impl Term {
             .unwrap_or(0.into())
     }
 
+    /// Returns the power of `var` in the term, 0 if it does not appear.
+    fn degree_of(&self, var: &str) -> Rational64 {
+        self.variables
+            .iter()
+            .filter(|v| v.name == var)
+            .map(|v| v.degree)
+            .sum()
+    }
+
+    /// Returns the total degree of the term, the sum of the powers of its variables.
+    pub fn total_degree(&self) -> Rational64 {
+        self.variables.iter().map(|v| v.degree).sum()
+    }
+
+    /// Iterates over the variables in the term in ascending order of their names, like `sort_vars` would leave them.
+    pub fn iter_vars(&self) -> std::vec::IntoIter<&Variable> {
+        let mut vars: Vec<&Variable> = self.variables.iter().collect();
+        vars.sort_by(|a, b| a.name.cmp(&b.name));
+        vars.into_iter()
+    }
+
     /// Sorts the variables in the term in ascending order based on their names.
     pub fn sort_vars(&mut self) {
         self.variables.sort_by(|a, b| a.name.cmp(&b.name));
@@ -44,7 +782,10 @@ impl Term {
             let mut found = false;
             for var2 in &mut new_vars {
                 if var1.name == var2.name {
-                    var2.degree += var1.degree;
+                    var2.degree = var2
+                        .degree
+                        .try_add(&var1.degree)
+                        .unwrap_or_else(|| overflow());
                     found = true;
                     break;
                 }
@@ -57,51 +798,145 @@ impl Term {
         self.variables = new_vars;
     }
 
+    /// Converts the term to a string in a pretty format, omitting a coefficient of 1 or -1 when there are variables.
+    pub fn as_string(&self) -> String {
+        let mut result = String::new();
+        if self.variables.is_empty() || self.coefficient != T::one() {
+            if self.coefficient == -T::one() && !self.variables.is_empty() {
+                result.push('-');
+            } else {
+                result.push_str(&self.coefficient.to_string());
+            }
+        }
+        for (j, variable) in self.variables.iter().enumerate() {
+            // Separate multi-character names so that `x1*x2` is not read back as `x1x2`
+            if j != 0
+                && (variable.name.chars().count() > 1
+                    || self.variables[j - 1].name.chars().count() > 1)
+            {
+                result.push('*');
+            }
+            result.push_str(&variable.name);
+            if variable.degree != 1.into() {
+                result.push_str(&format!("^({})", variable.degree));
+            }
+        }
+        result
+    }
+}
+
+impl Term {
+    /// Returns the constant term `c`.
+    pub fn constant(c: Coeff) -> Term {
+        Term {
+            coefficient: c,
+            variables: vec![],
+        }
+    }
+
+    /// Returns the term made of the variable `name` alone, with coefficient 1.
+    pub fn var(name: &str) -> Term {
+        Term {
+            coefficient: Coeff::one(),
+            variables: vec![Variable {
+                name: name.to_string(),
+                degree: 1.into(),
+            }],
+        }
+    }
+
+    /// True if the term divides `other`: every variable in it appears in `other` with at least the same power.
+    fn divides(&self, other: &Term) -> bool {
+        !self.coefficient.is_zero()
+            && self
+                .variables
+                .iter()
+                .all(|v| other.degree_of(&v.name) >= self.degree_of(&v.name))
+    }
+
     /// Inverts the term.
     pub fn invert(&mut self) {
-        self.coefficient = Rational64::new(*self.coefficient.denom(), *self.coefficient.numer());
+        self.coefficient = self.coefficient.recip();
         for var in &mut self.variables {
             var.degree *= -1;
         }
     }
 
-    /// Returns a polynomial containing the term to the power of q.
-    pub fn pow(&self, q: Rational64) -> Polynomial {
-        let mut new_vars: Vec<Variable> = Vec::new();
-        for var in &self.variables {
-            new_vars.push(Variable {
-                name: var.name.clone(),
-                degree: var.degree * q.clone(),
-            });
+    /// Multiplies two terms like `*`, but returns an error instead of panicking if the coefficient overflows.
+    pub fn checked_mul(&self, other: &Term) -> Result<Term, PolyError> {
+        let coefficient = self
+            .coefficient
+            .checked_mul(&other.coefficient)
+            .ok_or(PolyError::CoefficientOverflow)?;
+        if coefficient.is_zero() {
+            return Ok(Term::constant(coefficient));
         }
-        let ratio_coef =
-            Rational64::from_f64(self.coefficient.to_f64().unwrap().powf(q.to_f64().unwrap()))
-                .unwrap();
-        if self.coefficient.denom() == &1 && ratio_coef.denom() != &1 {
-            // Don't convert expressions like sqrt(13) to a ratio
-            return Polynomial {
+        let mut variables = self.variables.clone();
+        variables.extend(other.variables.iter().cloned());
+        let mut term = Term {
+            coefficient,
+            variables,
+        };
+        term.sort_vars();
+        term.factor();
+        Ok(term)
+    }
+
+    /// Returns a polynomial containing the term to the power of q. The coefficient is raised exactly: whole powers always
+    /// are, and roots are when the result is rational, like 4^(1/2) = 2. Otherwise, like 13^(1/2) or (-4)^(1/2), the term
+    /// is kept as it is with q as a pending power, so nothing is lost to rounding.
+    ///
+    /// ```
+    /// use num::rational::Rational64;
+    /// use sym_tfg::polynomial::{coeff, Coeff, Term};
+    ///
+    /// let pow = |c: Coeff, q: Rational64| Term::constant(c).pow(q);
+    /// assert_eq!(pow(coeff(2) / coeff(3), 3.into()).as_string(), "8/27");
+    /// assert_eq!(pow(coeff(2) / coeff(3), (-2).into()).as_string(), "9/4");
+    /// assert_eq!(pow(coeff(4), Rational64::new(1, 2)).as_string(), "2");
+    /// assert_eq!(pow(coeff(13), Rational64::new(1, 2)).as_string(), "(13)^(1/2)");
+    /// ```
+    pub fn pow(&self, q: Rational64) -> Polynomial {
+        let coefficient = match exact_pow(self.coefficient.clone(), q) {
+            Some(c) => Some(c),
+            // A whole power that does not fit in a `Coeff` overflows like any other arithmetic on coefficients
+            None if q.is_integer() && !self.coefficient.is_zero() => overflow(),
+            None => None,
+        };
+        match coefficient {
+            Some(coefficient) => Polynomial {
+                terms: vec![Term {
+                    coefficient,
+                    variables: self
+                        .variables
+                        .iter()
+                        .map(|var| Variable {
+                            name: var.name.clone(),
+                            degree: var.degree * q,
+                        })
+                        .collect(),
+                }],
+                pending_pow: 1.into(),
+            },
+            None => Polynomial {
                 terms: vec![self.clone()],
-                degree: q,
-            };
-        }
-        Polynomial {
-            terms: vec![Term {
-                coefficient: ratio_coef,
-                variables: new_vars,
-            }],
-            degree: 1.into(),
+                pending_pow: q,
+            },
         }
     }
 }
 
-impl Mul for Term {
-    type Output = Term;
-    fn mul(self, other: Self) -> Term {
+impl<T: Coefficient> Mul for Term<T> {
+    type Output = Term<T>;
+    fn mul(self, other: Self) -> Term<T> {
         let mut result = Vec::new();
         let mut new_vars = self.variables.clone();
         new_vars.extend(other.variables.clone());
         let mut new_term = Term {
-            coefficient: self.coefficient * other.coefficient,
+            coefficient: self
+                .coefficient
+                .try_mul(&other.coefficient)
+                .unwrap_or_else(|| overflow()),
             variables: new_vars,
         };
         new_term.sort_vars();
@@ -109,7 +944,7 @@ impl Mul for Term {
         result.push(new_term);
         let mut product = Polynomial {
             terms: result,
-            degree: 1.into(),
+            pending_pow: 1.into(),
         };
         product.simplify();
         product.terms[0].clone()
@@ -121,11 +956,11 @@ impl Div for Term {
     fn div(self, other: Self) -> Polynomial {
         let dividend = Polynomial {
             terms: vec![self],
-            degree: 1.into(),
+            pending_pow: 1.into(),
         };
         let divisor = Polynomial {
             terms: vec![other],
-            degree: 1.into(),
+            pending_pow: 1.into(),
         };
         let mut result = Vec::new();
         for term1 in &dividend.terms {
@@ -136,7 +971,11 @@ impl Div for Term {
                 }
                 new_vars.extend(term1.variables.clone());
                 let mut new_term = Term {
-                    coefficient: term1.coefficient / term2.coefficient,
+                    coefficient: match term1.coefficient.checked_div(&term2.coefficient) {
+                        Some(c) => c,
+                        None if term2.coefficient.is_zero() => panic!("division of a term by zero"),
+                        None => overflow(),
+                    },
                     variables: new_vars,
                 };
                 new_term.sort_vars();
@@ -146,14 +985,23 @@ impl Div for Term {
         }
         let mut quotient = Polynomial {
             terms: result,
-            degree: 1.into(),
+            pending_pow: 1.into(),
         };
         quotient.simplify();
         quotient
     }
 }
 
-impl PartialEq for Term {
+impl<T: Coefficient> Neg for Term<T> {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        self.coefficient = self.coefficient.try_neg().unwrap_or_else(|| overflow());
+        self
+    }
+}
+
+impl<T: Coefficient> PartialEq for Term<T> {
     fn eq(&self, other: &Self) -> bool {
         let mut self_copy = self.clone();
         self_copy.sort_vars();
@@ -164,133 +1012,127 @@ impl PartialEq for Term {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Polynomial {
-    pub terms: Vec<Term>,
-    pub degree: Rational64,
+impl<T: Coefficient> Term<T> {
+    /// Compares the monomials of two terms, ignoring the coefficients. The larger monomial in the default
+    /// `MonomialOrder` is the greater one, and different ways of writing the same monomial, like x*x and x^2,
+    /// are told apart by their sorted variables so only terms with equal variables compare equal.
+    fn cmp_monomial(&self, other: &Term<T>) -> Ordering {
+        let mut self_copy = self.clone();
+        self_copy.sort_vars();
+        let mut other_copy = other.clone();
+        other_copy.sort_vars();
+        MonomialOrder::default()
+            .compare(other, self)
+            .then_with(|| self_copy.variables.cmp(&other_copy.variables))
+    }
 }
 
-impl Polynomial {
-    /// Return the degree of the polynomial.
-    pub fn degree(&self) -> Rational64 {
-        self.terms
-            .iter()
-            .map(|t| t.max_degree())
-            .max()
-            .unwrap_or(0.into())
+/// Orders terms by their monomial, the larger one in the default `MonomialOrder` being the greater, then by coefficient.
+impl<T: Coefficient + Ord> Ord for Term<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_monomial(other)
+            .then_with(|| self.coefficient.cmp(&other.coefficient))
     }
+}
 
-    /// List each term in the polynomial.
-    pub fn _print(&self) {
-        for term in &self.terms {
-            println!("{:?}", term);
-        }
+impl<T: Coefficient + Ord> PartialOrd for Term<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
+}
 
-    /// Return the leading term of the polynomial.
-    pub fn leading_term(&self) -> Term {
-        let mut max_degree = Rational64::new(0, 1);
-        let mut leading_term = Term {
-            coefficient: Rational64::new(0, 1),
-            variables: Vec::new(),
-        };
-        for term in &self.terms {
-            let degree = term.max_degree();
-            if degree >= max_degree {
-                max_degree = degree;
-                leading_term = term.clone();
-            }
-        }
-        leading_term
+impl<T: Coefficient + Ord> Eq for Term<T> {}
+
+#[derive(Debug, Clone)]
+pub struct Polynomial<T = Coeff> {
+    pub terms: Vec<Term<T>>,
+    /// A power the whole polynomial is raised to that has not been applied to the terms yet, shown as `(...)^(q)`.
+    /// It is 1 for almost every polynomial; `apply_pow` expands it when it can.
+    pub pending_pow: Rational64,
+}
+
+impl<T: Coefficient> Polynomial<T> {
+    /// Sorts the terms in the polynomial in the default `MonomialOrder`, graded lexicographic.
+    pub fn sort_terms(&mut self) -> () {
+        self.sort_terms_by(MonomialOrder::default());
     }
 
-    /// Evaluate the polynomial at a given value for the variables.
-    pub fn evaluate(&mut self, values: &Vec<(String, Rational64)>) {
-        let mut result = Polynomial {
-            terms: Vec::new(),
-            degree: 1.into(),
-        };
-        for term in &self.terms {
-            let mut new_term = term.clone();
-            for var in &mut new_term.variables {
-                if let Some(val) = values.iter().find(|(name, _)| name == &var.name) {
-                    let value = *val.1.clone().numer() as f64 / *val.1.denom() as f64;
-                    let expon = *var.degree.numer() as f64 / *var.degree.denom() as f64;
-                    new_term.coefficient =
-                        new_term.coefficient * Rational64::from_f64(value.powf(expon)).unwrap();
-                    var.degree = 0.into(); // Set the degree of the variable to 0, essentially removing it from the term
-                }
-            }
-            result.terms.push(new_term);
-        }
-        *self = result;
-        self.simplify();
+    /// Sorts the terms in the polynomial in the given order.
+    pub fn sort_terms_by(&mut self, order: MonomialOrder) {
+        self.terms.sort_by(|a, b| order.compare(a, b));
     }
 
-    /// Sorts the terms in the polynomial in descending order based on the max degree of the variables in each term, then by alphabetical order.
-    pub fn sort_terms(&mut self) -> () {
-        self.terms.sort_by(|a, b| {
-            let max_degree_cmp = b
-                .variables
-                .iter()
-                .map(|v| v.degree)
-                .max()
-                .unwrap_or(0.into())
-                .cmp(
-                    &a.variables
-                        .iter()
-                        .map(|v| v.degree)
-                        .max()
-                        .unwrap_or(0.into()),
-                );
-            if max_degree_cmp != Ordering::Equal {
-                return max_degree_cmp;
-            }
-            a.variables.cmp(&b.variables)
-        });
+    /// Iterates over the terms in the same order `sort_terms` would put them in, without changing the polynomial.
+    pub fn iter(&self) -> std::vec::IntoIter<&Term<T>> {
+        self.iter_by(MonomialOrder::default())
+    }
+
+    /// Iterates over the terms in the given order, without changing the polynomial.
+    pub fn iter_by(&self, order: MonomialOrder) -> std::vec::IntoIter<&Term<T>> {
+        let mut terms: Vec<&Term<T>> = self.terms.iter().collect();
+        terms.sort_by(|a, b| order.compare(a, b));
+        terms.into_iter()
     }
 
     /// Converts the polynomial to a string in a pretty format.
     pub fn as_string(&self) -> String {
+        self.as_string_by(MonomialOrder::default())
+    }
+
+    /// Converts the polynomial to a string like `as_string`, with the terms in the given order.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::MonomialOrder;
+    ///
+    /// let mut p = poly!(z^2 + x y^2 + x^2 z + y^3 z + x^3 + x^2 y);
+    /// assert_eq!(p.as_string_by(MonomialOrder::Lex), "x^(3)+x^(2)y+x^(2)z+xy^(2)+y^(3)z+z^(2)");
+    /// assert_eq!(p.as_string_by(MonomialOrder::GradedLex), "y^(3)z+x^(3)+x^(2)y+x^(2)z+xy^(2)+z^(2)");
+    /// assert_eq!(p.as_string_by(MonomialOrder::GradedRevLex), "y^(3)z+x^(3)+x^(2)y+xy^(2)+x^(2)z+z^(2)");
+    ///
+    /// p.sort_terms_by(MonomialOrder::Lex);
+    /// assert_eq!(p.terms[0], poly!(x^3).terms[0]);
+    /// assert_eq!(p.terms[5], poly!(z^2).terms[0]);
+    /// ```
+    pub fn as_string_by(&self, order: MonomialOrder) -> String {
         let mut result = String::new();
-        for (i, term) in self.terms.iter().enumerate() {
-            if term.coefficient == Rational64::new(0, 1) && self.terms.len() > 1 {
+        for (i, term) in self.iter_by(order).enumerate() {
+            if term.coefficient.is_zero() && self.terms.len() > 1 {
                 continue;
             }
-            if i != 0 && term.coefficient > Rational64::new(0, 1) {
+            if i != 0 && term.coefficient > T::zero() {
                 result.push_str("+");
             }
-            if term.variables.is_empty() || term.coefficient != Rational64::new(1, 1) {
-                if term.coefficient == Rational64::new(-1, 1) && !term.variables.is_empty() {
-                    result.push_str("-");
-                } else {
-                    result.push_str(&term.coefficient.to_string());
-                }
-            }
-            for variable in &term.variables {
-                result.push_str(&variable.name);
-                if variable.degree != 1.into() {
-                    result.push_str(&format!("^({})", variable.degree));
-                }
-            }
+            result.push_str(&term.as_string());
         }
-        if self.degree != 1.into() {
-            result = format!("({})^({})", result, self.degree);
+        if self.pending_pow != 1.into() {
+            result = format!("({})^({})", result, self.pending_pow);
         }
         result
     }
 
     /// Adds like terms in the polynomial.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::Polynomial;
+    ///
+    /// let mut p: Polynomial = "x1 + x2 + x1 + x_1^3 + x_1^3".parse().unwrap();
+    /// p.add_like_terms();
+    /// assert_eq!(p.as_string(), "2x_1^(3)+2x1+x2");
+    /// ```
     pub fn add_like_terms(&mut self) -> () {
-        let mut new_terms: Vec<Term> = Vec::new();
+        let mut new_terms: Vec<Term<T>> = Vec::new();
 
         for term in &self.terms {
-            let coeff: Rational64 = term.coefficient.clone();
+            let coeff: T = term.coefficient.clone();
             let mut found = false;
 
             for term1 in &mut new_terms {
                 if term1.variables == term.variables {
-                    term1.coefficient += term.coefficient;
+                    term1.coefficient = term1
+                        .coefficient
+                        .try_add(&term.coefficient)
+                        .unwrap_or_else(|| overflow());
                     found = true;
                     break;
                 }
@@ -308,24 +1150,8 @@ impl Polynomial {
     }
 
     /// Simplifies the polynomial by sorting the terms, sorting the variables in each term, factoring each term, and adding like terms.
+    /// The pending power is left untouched, so simplifying twice gives the same result; use `apply_pow` to apply it.
     pub fn simplify(&mut self) -> () {
-        let d: Option<f64> = self.degree.to_f64();
-        // println!("Degree: {:?}", d);
-        if d.is_some() {
-            if self.terms.len() == 1 {
-                let exp = Rational64::from_f64(d.unwrap());
-                let powered = self.terms[0].pow(exp.unwrap()); // TODO: Here, sqrt(13) becomes a ratio
-                self.terms = powered.terms;
-                self.degree = powered.degree;
-            } else if d.unwrap().fract() == 0.0 && d.unwrap() >= 2.0 {
-                for _i in 1..d.unwrap() as i64 {
-                    *self = self.clone() * self.clone();
-                }
-                self.degree = 1.into();
-            }
-        }
-        // println!("Simplifying 1: {}", self.as_string());
-
         for term in &mut self.terms {
             term.sort_vars();
         }
@@ -337,13 +1163,12 @@ impl Polynomial {
         self.add_like_terms();
 
         // Filter to remove terms with coefficient 0
-        self.terms
-            .retain(|term| term.coefficient != Rational64::new(0, 1));
+        self.terms.retain(|term| !term.coefficient.is_zero());
 
         // Add a term with coefficient 0 if all terms were removed
         if self.terms.is_empty() {
             self.terms.push(Term {
-                coefficient: Rational64::new(0, 1),
+                coefficient: T::zero(),
                 variables: vec![],
             });
         }
@@ -351,170 +1176,2261 @@ impl Polynomial {
         self.sort_terms();
     }
 
-    /// Multiplies the polynomial by the smallest scalar such that all coefficients are integers. Returns the scalar.
-    pub fn make_integer(&mut self) -> i64 {
-        // Get the lcm of the denominators of the coefficients
-        let mut lcm = 1;
-        for term in &self.terms {
-            let denom = term.coefficient.denom();
-            lcm = num_integer::lcm(lcm as i64, *denom);
+    /// Return the degree of the polynomial, or `None` for the zero polynomial, whose degree is taken to be -infinity.
+    /// Since `None` is less than any `Some`, comparing degrees works the same way with the zero polynomial.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// assert_eq!(poly!(x^3 - x).degree(), Some(3.into()));
+    /// assert_eq!(poly!(5).degree(), Some(0.into()));
+    /// assert_eq!(poly!(0 x^2).degree(), None);
+    /// assert!(poly!(0).degree() < poly!(5).degree());
+    ///
+    /// // Dividing the zero polynomial gives zero, and dividing by it leaves the dividend as the remainder
+    /// assert_eq!(poly!(0).div_rem(&poly!(x + 1)), (poly!(0), poly!(0)));
+    /// assert_eq!(poly!(x + 1).div_rem(&poly!(0)), (poly!(0), poly!(x + 1)));
+    /// ```
+    pub fn degree(&self) -> Option<Rational64> {
+        self.terms
+            .iter()
+            .filter(|t| !t.coefficient.is_zero())
+            .map(|t| t.max_degree())
+            .max()
+    }
+
+    /// Returns the canonical form of the polynomial: variables sorted in each term, like terms added, zero terms removed
+    /// and terms sorted in the default `MonomialOrder`. Equal polynomials have the same normalized form term by term.
+    pub fn normalized(&self) -> Polynomial<T> {
+        let mut p = self.clone();
+        p.simplify();
+        p
+    }
+
+    /// Returns true if the polynomial is zero once simplified, like 3x-3x. A polynomial without terms is zero too.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// assert!(poly!().is_zero());
+    /// assert!(poly!(0).is_zero());
+    /// assert!(poly!(3 x - 3 x).is_zero());
+    /// assert!(!poly!(3 x - 2 x).is_zero());
+    /// ```
+    pub fn is_zero(&self) -> bool {
+        if self.terms.iter().all(|t| t.coefficient.is_zero()) {
+            return true;
         }
-        // Multiply each coefficient by the lcm
-        for term in &mut self.terms {
-            term.coefficient *= Rational64::new(lcm, 1);
+        if self.terms.len() == 1 {
+            return false;
         }
-        lcm
+        let mut p = self.clone();
+        p.simplify();
+        p.terms.iter().all(|t| t.coefficient.is_zero())
     }
+}
 
-    /// Finds the greatest common divisor of the coefficients of the terms in a single-variable polynomial with integer coefficients. Returns the gcd and the polynomial with the gcd factored out.
-    pub fn factor(&mut self) -> (Term, Polynomial) {
-        if self.degree != 1.into() {
-            return (
-                Term {
-                    coefficient: Rational64::new(1, 1),
-                    variables: vec![],
-                },
-                self.clone(),
-            ); // Only works for degree 1 polynomials
+impl Polynomial {
+    /// Returns the constant polynomial `c`, already simplified.
+    pub fn constant(c: Coeff) -> Polynomial {
+        Polynomial {
+            terms: vec![Term::constant(c)],
+            pending_pow: 1.into(),
         }
-        let mut factored_out = Term {
-            coefficient: Rational64::new(1, 1),
-            variables: vec![],
+    }
+
+    /// Returns the polynomial made of the variable `name` alone, already simplified.
+    pub fn var(name: &str) -> Polynomial {
+        Polynomial {
+            terms: vec![Term::var(name)],
+            pending_pow: 1.into(),
+        }
+    }
+
+    /// Returns the polynomial with a single term, `coefficient` times each variable raised to its power, already simplified:
+    /// repeated variables are combined and variables to the power of 0 are left out.
+    pub fn monomial(coefficient: Coeff, variables: &[(&str, Rational64)]) -> Polynomial {
+        let mut term = Term {
+            coefficient: coefficient.clone(),
+            variables: variables
+                .iter()
+                .map(|(name, degree)| Variable {
+                    name: name.to_string(),
+                    degree: *degree,
+                })
+                .collect(),
         };
-        let mut factored = self.clone();
+        term.sort_vars();
+        term.factor();
+        if coefficient.is_zero() {
+            term.variables.clear();
+        }
+        Polynomial {
+            terms: vec![term],
+            pending_pow: 1.into(),
+        }
+    }
+
+    /// Returns an empty `PolynomialBuilder`, to add terms to one by one.
+    pub fn builder() -> PolynomialBuilder {
+        PolynomialBuilder::default()
+    }
 
-        // Find the gcd of the coefficients
-        let mut gcd = self.terms[0].coefficient.numer().abs();
+    /// Return the highest power of the variable `var` in the polynomial.
+    pub fn degree_in(&self, var: &str) -> Rational64 {
+        self.terms
+            .iter()
+            .filter(|t| !t.coefficient.is_zero())
+            .flat_map(|t| t.variables.iter())
+            .filter(|v| v.name == var)
+            .map(|v| v.degree)
+            .max()
+            .unwrap_or(0.into())
+    }
+
+    /// List each term in the polynomial.
+    pub fn _print(&self) {
         for term in &self.terms {
-            gcd = num_integer::gcd(gcd, term.coefficient.numer().abs());
+            println!("{:?}", term);
         }
-        factored_out.coefficient = Rational64::new(gcd, 1);
+    }
 
-        // Check the name of the variable that appears in all terms
-        let mut seen_vars = vec![];
-        for var in &self.terms[0].variables {
-            seen_vars.push(var.name.clone());
-        }
+    /// Return the leading term of the polynomial.
+    pub fn leading_term(&self) -> Term {
+        let mut max_degree = Rational64::new(0, 1);
+        let mut leading_term = Term {
+            coefficient: Coeff::zero(),
+            variables: Vec::new(),
+        };
         for term in &self.terms {
-            let mut curr_vars = vec![];
-            for var in &term.variables {
-                curr_vars.push(var.name.clone());
+            let degree = term.max_degree();
+            if degree >= max_degree {
+                max_degree = degree;
+                leading_term = term.clone();
             }
-            seen_vars = seen_vars
-                .iter()
-                .map(|x| x.clone())
-                .filter(|x| curr_vars.contains(x))
-                .collect();
+        }
+        leading_term
+    }
 
-            if seen_vars.len() == 0 {
-                if gcd != 0 {
-                    for term in &mut factored.terms {
-                        // println!("{:?}", term);
-                        // println!("{:?}", gcd);
-                        term.coefficient *= Rational64::new(1, gcd);
-                    }
+    /// Returns the term with the highest power of `var`, or the first of them in the default `MonomialOrder` if several have it.
+    /// Terms with a coefficient of 0 are skipped, and the zero polynomial gives a zero term.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::MonomialOrder;
+    ///
+    /// for p in [poly!(x^2 y + x y^3 + x^2), poly!(x^2 + x y^3 + x^2 y)] {
+    ///     assert_eq!(p.leading_term_in("x"), poly!(x^2 y).terms[0]);
+    ///     assert_eq!(p.leading_term_in("y"), poly!(x y^3).terms[0]);
+    ///     assert_eq!(p.leading_term_by(MonomialOrder::Lex), poly!(x^2 y).terms[0]);
+    /// }
+    ///
+    /// // The quotient does not depend on the order the terms are written in
+    /// for p in [poly!(x^2 y + x), poly!(x + x^2 y)] {
+    ///     assert_eq!(p.div_rem(&poly!(x)), (poly!(x y + 1), poly!(0)));
+    /// }
+    /// ```
+    pub fn leading_term_in(&self, var: &str) -> Term {
+        self.terms
+            .iter()
+            .filter(|t| !t.coefficient.is_zero())
+            .min_by(|a, b| {
+                b.degree_of(var)
+                    .cmp(&a.degree_of(var))
+                    .then_with(|| MonomialOrder::default().compare(a, b))
+            })
+            .cloned()
+            .unwrap_or_else(|| Term::constant(Coeff::zero()))
+    }
+
+    /// Returns the first term in the given order, skipping terms with a coefficient of 0. The zero polynomial gives a zero term.
+    pub fn leading_term_by(&self, order: MonomialOrder) -> Term {
+        self.terms
+            .iter()
+            .filter(|t| !t.coefficient.is_zero())
+            .min_by(|a, b| order.compare(a, b))
+            .cloned()
+            .unwrap_or_else(|| Term::constant(Coeff::zero()))
+    }
+
+    /// Replaces the variables that have a value with it, leaving the others as they are. Powers of the values are exact:
+    /// whole powers always are, and roots are when the result is rational, like (4/9)^(1/2) = 2/3. A polynomial that is a
+    /// single power whose root is not rational, like x^(1/2) at x = 2/3, becomes that root, (2/3)^(1/2); elsewhere such a
+    /// root is an error, and so are even roots of negative values and coefficients that overflow. The polynomial is left
+    /// unchanged on error.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::{coeff, PolyError, Polynomial};
+    ///
+    /// let at = |value: i64| vec![("x".to_string(), coeff(value))];
+    /// let mut p: Polynomial = "x^(1/2) + y".parse().unwrap();
+    /// p.evaluate(&at(9)).unwrap();
+    /// assert_eq!(p, "3 + y".parse().unwrap());
+    ///
+    /// let mut p: Polynomial = "x^(1/2) + y".parse().unwrap();
+    /// assert!(matches!(p.evaluate(&at(-9)), Err(PolyError::NotReal { .. })));
+    /// ```
+    pub fn evaluate(&mut self, values: &[(String, Coeff)]) -> Result<(), PolyError> {
+        if let Some(root) = self.evaluate_root(values)? {
+            *self = root;
+            return Ok(());
+        }
+        // With every variable bound and raised to a whole power, Horner's rule gives the value without building terms
+        let bound =
+            |v: &Variable| v.degree.is_integer() && values.iter().any(|(name, _)| *name == v.name);
+        if self.pending_pow == 1.into() && self.terms.iter().flat_map(|t| &t.variables).all(bound) {
+            *self = Polynomial::constant(self.eval_at(values)?);
+            return Ok(());
+        }
+        let mut result = Polynomial {
+            terms: Vec::new(),
+            pending_pow: self.pending_pow,
+        };
+        for term in self.iter() {
+            let mut new_term = term.clone();
+            for var in &mut new_term.variables {
+                if let Some((_, value)) = values.iter().find(|(name, _)| name == &var.name) {
+                    let power = exact_power(&var.name, var.degree, value)?;
+                    new_term.coefficient = new_term
+                        .coefficient
+                        .try_mul(&power)
+                        .ok_or(PolyError::CoefficientOverflow)?;
+                    var.degree = 0.into(); // Set the degree of the variable to 0, essentially removing it from the term
                 }
-                return (factored_out, factored); // No common variable, return the original polynomial
             }
+            result.terms.push(new_term);
         }
-        let var_name = seen_vars[0].clone(); // Always the first element
-                                             // TODO: Maybe add an option to this function to specify the variable to factor out
-
-        // Make the coefficients integers
-        let mut p = self.clone();
-        let adjust = p.make_integer();
-        p.simplify();
+        catch_overflow(|| result.apply_pow())?;
+        *self = result;
+        Ok(())
+    }
 
-        // Find the gcd of the coefficients
-        let mut gcd = p.terms[0].coefficient.numer().abs();
-        for term in &p.terms {
-            gcd = num_integer::gcd(gcd, term.coefficient.numer().abs());
+    /// Evaluates a polynomial that is a single term c x^q, where x has a value a whose power is not rational, as the
+    /// root (k a)^q for the rational k with k^q = c. Returns `None` for any other polynomial, or if k is not rational.
+    fn evaluate_root(&self, values: &[(String, Coeff)]) -> Result<Option<Polynomial>, PolyError> {
+        if self.pending_pow != 1.into() || self.terms.len() != 1 {
+            return Ok(None);
         }
+        let term = &self.terms[0];
+        let [var] = term.variables.as_slice() else {
+            return Ok(None);
+        };
+        let value = match values.iter().find(|(name, _)| *name == var.name) {
+            Some((_, value)) => value,
+            None => return Ok(None),
+        };
+        if var.degree.is_integer() || exact_pow(value.clone(), var.degree).is_some() {
+            return Ok(None);
+        }
+        let base = match exact_pow(term.coefficient.clone(), var.degree.recip()) {
+            Some(k) => k.try_mul(value).ok_or(PolyError::CoefficientOverflow)?,
+            None => return Ok(None),
+        };
+        if base.is_negative() && var.degree.denom().is_even() {
+            return Err(PolyError::NotReal {
+                var: var.name.clone(),
+                degree: var.degree,
+                value: value.clone(),
+            });
+        }
+        Ok(Some(Term::constant(base).pow(var.degree)))
+    }
 
-        // Find the smallest power of the variable that appears in all terms
-        let mut min_degree = p.terms[0].variables[0].degree;
-        for term in &p.terms {
+    /// Evaluates the polynomial exactly, with Horner's rule on each variable. Every variable must be given a value
+    /// and appear with integer exponents only.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::{coeff, PolyError};
+    ///
+    /// let values = [("x".to_string(), coeff(2) / coeff(3)), ("y".to_string(), coeff(-1))];
+    /// assert_eq!(poly!(x^3 y - 3 x y^2 + 1).eval_at(&values).unwrap(), coeff(-35) / coeff(27));
+    /// assert!(matches!(poly!(x z).eval_at(&values), Err(PolyError::UnboundVariable { .. })));
+    /// ```
+    pub fn eval_at(&self, values: &[(String, Coeff)]) -> Result<Coeff, PolyError> {
+        let mut names: Vec<&str> = Vec::new();
+        for term in &self.terms {
             for var in &term.variables {
-                if var.degree < min_degree && var.degree > 0.into() {
-                    min_degree = var.degree;
+                if !names.contains(&var.name.as_str()) {
+                    if !values.iter().any(|(n, _)| *n == var.name) {
+                        return Err(PolyError::UnboundVariable {
+                            var: var.name.clone(),
+                        });
+                    }
+                    names.push(&var.name);
                 }
             }
         }
+        let terms: Vec<&Term> = self.terms.iter().collect();
+        let result = horner(&terms, &names, values)?;
+        if self.pending_pow == 1.into() {
+            return Ok(result);
+        }
+        exact_pow(result, self.pending_pow).ok_or_else(|| PolyError::FractionalExponent {
+            var: format!("({})", self.as_string()),
+            degree: self.pending_pow,
+        })
+    }
 
-        // Factor out the gcd
-        factored_out.coefficient = Rational64::new(gcd, 1);
-        factored_out.variables.push(Variable {
-            name: var_name,
-            degree: min_degree,
-        });
+    /// Evaluates the polynomial approximately with floating point numbers, without building any new polynomial.
+    /// Returns `None` if a variable has no value.
+    ///
+    /// ```
+    /// use num::ToPrimitive;
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::coeff;
+    ///
+    /// let p = poly!(3 x^2 y - 1/2 x + 4);
+    /// for (x, y) in [(0, 0), (1, -2), (-3, 5), (7, 4)] {
+    ///     let exact = p.eval_at(&[("x".to_string(), coeff(x)), ("y".to_string(), coeff(y))]).unwrap();
+    ///     let approx = p.evaluate_f64(&[("x", x as f64), ("y", y as f64)]).unwrap();
+    ///     assert_eq!(approx, exact.to_f64().unwrap());
+    /// }
+    /// assert_eq!(p.evaluate_f64(&[("x", 1.0)]), None);
+    /// ```
+    pub fn evaluate_f64(&self, values: &[(&str, f64)]) -> Option<f64> {
+        let mut result = 0.0;
+        for term in &self.terms {
+            let mut product = term.coefficient.to_f64()?;
+            for var in &term.variables {
+                let value = values.iter().find(|(name, _)| *name == var.name)?.1;
+                product *= powf64(value, var.degree)?;
+            }
+            result += product;
+        }
+        powf64(result, self.pending_pow)
+    }
 
-        let mut inv: Term = factored_out.clone();
-        inv.invert();
-        factored = factored
-            * Polynomial {
-                terms: vec![inv],
-                degree: 1.into(),
+    /// Replaces every occurrence of the variable `var` with the polynomial `value`, leaving the other variables as they are.
+    /// Negative powers of `var` can only be replaced by a single nonzero term.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::PolyError;
+    ///
+    /// let p = poly!(x y + y^2);
+    /// assert_eq!(p.substitute("y", &poly!(x + 1)).unwrap(), poly!(2 x^2 + 3 x + 1));
+    /// assert_eq!(p.substitute("z", &poly!(x + 1)).unwrap(), p);
+    ///
+    /// // y := x + 1, then x := 2 z
+    /// let chained = p.substitute("y", &poly!(x + 1)).unwrap().substitute("x", &poly!(2 z)).unwrap();
+    /// assert_eq!(chained, poly!(8 z^2 + 6 z + 1));
+    ///
+    /// let fractional = poly!(y^(1/2)).substitute("y", &poly!(x + 1));
+    /// assert!(matches!(fractional, Err(PolyError::FractionalExponent { .. })));
+    /// ```
+    pub fn substitute(&self, var: &str, value: &Polynomial) -> Result<Polynomial, PolyError> {
+        if !self.variables().iter().any(|name| name == var) {
+            return Ok(self.clone());
+        }
+        let mut p = self.clone();
+        p.apply_pow();
+        let mut result = Polynomial {
+            terms: vec![],
+            pending_pow: 1.into(),
+        };
+        for term in &p.terms {
+            let mut rest = Term {
+                coefficient: term.coefficient.clone(),
+                variables: vec![],
             };
+            let mut replaced = Polynomial {
+                terms: vec![Term {
+                    coefficient: Coeff::one(),
+                    variables: vec![],
+                }],
+                pending_pow: 1.into(),
+            };
+            for v in &term.variables {
+                if v.name != var {
+                    rest.variables.push(v.clone());
+                    continue;
+                }
+                if !v.degree.is_integer() {
+                    return Err(PolyError::FractionalExponent {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    });
+                }
+                let mut base = value.clone();
+                if v.degree < 0.into() {
+                    if base.terms.len() != 1 || base.terms[0].coefficient.is_zero() {
+                        return Err(PolyError::NotPolynomial {
+                            var: v.name.clone(),
+                            degree: v.degree,
+                        });
+                    }
+                    base.terms[0].invert();
+                }
+                replaced = match u32::try_from(v.degree.to_integer().unsigned_abs()) {
+                    Ok(exp) => replaced * base.powi(exp),
+                    Err(_) => {
+                        return Err(PolyError::NotPolynomial {
+                            var: v.name.clone(),
+                            degree: v.degree,
+                        })
+                    }
+                };
+            }
+            result += replaced
+                * Polynomial {
+                    terms: vec![rest],
+                    pending_pow: 1.into(),
+                };
+        }
+        // Keep a power on the whole polynomial that could not be expanded, like a square root
+        result.pending_pow = p.pending_pow;
+        result.apply_pow();
+        Ok(result)
+    }
 
-        // Undo the scaling of the coefficients
-        for term in &mut factored.terms {
-            term.coefficient *= Rational64::new(1, adjust);
+    /// Raises the polynomial to a whole power using exponentiation by squaring.
+    pub fn powi(&self, mut exp: u32) -> Polynomial {
+        let mut base = self.clone();
+        let mut result = Polynomial {
+            terms: vec![Term {
+                coefficient: Coeff::one(),
+                variables: vec![],
+            }],
+            pending_pow: 1.into(),
+        };
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result *= base.clone();
+            }
+            exp /= 2;
+            if exp > 0 {
+                base = base.clone() * base;
+            }
         }
-        factored_out.coefficient *= Rational64::new(1, adjust);
+        result.simplify();
+        result
+    }
 
-        factored.simplify();
+    /// Adds two polynomials like `+`, but returns an error instead of panicking if a coefficient overflows.
+    pub fn checked_add(&self, other: &Polynomial) -> Result<Polynomial, PolyError> {
+        let mut terms = self.terms.clone();
+        terms.extend(other.terms.iter().cloned());
+        Polynomial::checked_sum(terms)
+    }
 
-        return (factored_out, factored);
+    /// Subtracts two polynomials like `-`, but returns an error instead of panicking if a coefficient overflows.
+    pub fn checked_sub(&self, other: &Polynomial) -> Result<Polynomial, PolyError> {
+        self.checked_add(&-other.clone())
     }
 
-    /// Returns the name of the first variable in the polynomial.
-    pub fn first_var(&self) -> Option<String> {
-        if self.terms.len() == 0 {
-            panic!("Polynomial has no terms!");
-        } else if self.terms[0].variables.len() == 0 {
-            return None;
-        } else {
-            return Some(self.terms[0].variables[0].name.clone());
+    /// Multiplies two polynomials like `*`, but returns an error instead of panicking if a coefficient overflows.
+    pub fn checked_mul(&self, other: &Polynomial) -> Result<Polynomial, PolyError> {
+        let mut terms = Vec::with_capacity(self.terms.len() * other.terms.len());
+        for term1 in &self.terms {
+            for term2 in &other.terms {
+                terms.push(term1.checked_mul(term2)?);
+            }
         }
+        Polynomial::checked_sum(terms)
     }
 
-    /// Find symbolic coefficient by degree.
-    pub fn find_sym_coeff(&self, var: &str, degree: Rational64) -> (Term, Term) {
-        let zero = Term {
-            coefficient: Rational64::new(0, 1),
-            variables: vec![],
+    /// Builds the simplified sum of the terms like `simplify`, checking each addition of like terms for overflow.
+    fn checked_sum(terms: Vec<Term>) -> Result<Polynomial, PolyError> {
+        let mut sum: Vec<Term> = Vec::new();
+        for mut term in terms {
+            term.sort_vars();
+            term.factor();
+            match sum.iter_mut().find(|t| t.variables == term.variables) {
+                Some(t) => {
+                    t.coefficient = t
+                        .coefficient
+                        .checked_add(&term.coefficient)
+                        .ok_or(PolyError::CoefficientOverflow)?
+                }
+                None => sum.push(term),
+            }
+        }
+        let mut result = Polynomial {
+            terms: sum,
+            pending_pow: 1.into(),
         };
-        let term = self
+        result.simplify();
+        Ok(result)
+    }
+
+    /// Raises the polynomial to a whole power like `powi`, unless the result could have more than `max_terms` terms.
+    /// A power n of a polynomial with t terms has at most C(n+t-1, t-1) terms, one for each way of choosing n of them.
+    pub fn checked_pow(&self, exp: u32, max_terms: usize) -> Result<Polynomial, PolyError> {
+        let t = self
             .terms
             .iter()
-            .find(|t| {
-                t.variables
-                    .iter()
-                    .any(|v| v.name == var && v.degree == degree)
-            })
-            // .unwrap_or_else(|| panic!("No term with {}^{}", var, degree))
-            .unwrap_or_else(|| &zero)
-            .clone();
-        let sym_coeff = Term {
-            coefficient: term.coefficient.clone(),
+            .filter(|t| !t.coefficient.is_zero())
+            .count() as u128;
+        let n = exp as u128;
+        let k = t.saturating_sub(1).min(n);
+        let mut bound: u128 = 1;
+        for i in 1..=k {
+            match bound.checked_mul(n + t - 1 - k + i) {
+                Some(b) => bound = b / i,
+                None => {
+                    bound = u128::MAX;
+                    break;
+                }
+            }
+        }
+        if bound > max_terms as u128 {
+            return Err(PolyError::TooManyTerms {
+                terms: bound,
+                limit: max_terms,
+            });
+        }
+        Ok(self.powi(exp))
+    }
+
+    /// Raises the polynomial to a whole power like `powi`. A binomial is expanded directly with the binomial theorem,
+    /// (a+b)^n = sum of C(n,k) a^(n-k) b^k, which builds the n+1 terms instead of multiplying whole polynomials.
+    pub fn expand_pow(&self, n: u32) -> Polynomial {
+        let mut p = self.clone();
+        p.simplify();
+        if p.pending_pow != 1.into() || p.terms.len() != 2 {
+            return self.powi(n);
+        }
+        let power = |term: &Term, k: u32| Term {
+            coefficient: num::checked_pow(term.coefficient.clone(), k as usize)
+                .unwrap_or_else(|| overflow()),
             variables: term
                 .variables
                 .iter()
-                .filter(|v| v.name != var)
-                .cloned()
+                .map(|v| Variable {
+                    name: v.name.clone(),
+                    degree: v.degree * Rational64::from(k as i64),
+                })
                 .collect(),
         };
-        return (term, sym_coeff);
+        let (a, b) = (&p.terms[0], &p.terms[1]);
+        let mut terms = Vec::with_capacity(n as usize + 1);
+        // C(n,k), from Pascal's rule C(n,k+1) = C(n,k) (n-k)/(k+1)
+        let mut binomial = Coeff::one();
+        for k in 0..=n {
+            let (left, right) = (power(a, n - k), power(b, k));
+            let mut variables = left.variables;
+            variables.extend(right.variables);
+            terms.push(Term {
+                coefficient: binomial
+                    .try_mul(&left.coefficient)
+                    .and_then(|c| c.try_mul(&right.coefficient))
+                    .unwrap_or_else(|| overflow()),
+                variables,
+            });
+            binomial = binomial
+                .try_mul(&coeff((n - k) as i64))
+                .and_then(|c| c.checked_div(&coeff(k as i64 + 1)))
+                .unwrap_or_else(|| overflow());
+        }
+        let mut result = Polynomial {
+            terms,
+            pending_pow: 1.into(),
+        };
+        result.simplify();
+        result
     }
 
-    /// Finds the roots (numerical or symbolic) of the polynomial.
-    pub fn roots(&self, var: &str) -> Vec<Vec<PolyRatio>> {
-        let mut result = vec![Vec::new()];
-        let mut self_copy = self.clone();
-        self_copy.simplify();
-
-        // Find out the degree of the polynomial, but only taking into account the variable var
-        let degree = self_copy
+    /// Composes the polynomial with `inner`, replacing each power of `var` with the same power of `inner`.
+    /// Only whole, non-negative powers of `var` can be replaced.
+    pub fn compose(&self, var: &str, inner: &Polynomial) -> Result<Polynomial, PolyError> {
+        let mut p = self.clone();
+        p.simplify();
+        let mut result = Polynomial {
+            terms: vec![],
+            pending_pow: 1.into(),
+        };
+        for term in &p.terms {
+            let mut rest = term.clone();
+            rest.variables.retain(|v| v.name != var);
+            let mut replaced = Polynomial {
+                terms: vec![rest],
+                pending_pow: 1.into(),
+            };
+            if let Some(v) = term.variables.iter().find(|v| v.name == var) {
+                if !v.degree.is_integer() {
+                    return Err(PolyError::FractionalExponent {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    });
+                }
+                let exp =
+                    u32::try_from(v.degree.to_integer()).map_err(|_| PolyError::NotPolynomial {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    })?;
+                replaced *= inner.powi(exp);
+            }
+            result += replaced;
+        }
+        result.simplify();
+        Ok(result)
+    }
+
+    /// Returns the polynomial with `var` replaced by `var + a`, leaving the other variables as they are.
+    /// The coefficients of each power of `var` are shifted with Horner's rule (a Taylor shift), which is much cheaper
+    /// than expanding every `(var + a)^k`. Only whole, non-negative powers of `var` can be shifted.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::coeff;
+    ///
+    /// assert_eq!(poly!(x^2).shift("x", coeff(-1)).unwrap(), poly!(x^2 - 2 x + 1));
+    ///
+    /// let p = poly!(x^3 y - 1/2 x + y^2);
+    /// let a = coeff(2) / coeff(3);
+    /// assert_eq!(p.shift("x", a.clone()).unwrap().shift("x", -a).unwrap(), p);
+    /// assert!(poly!(x^(1/2)).shift("x", coeff(1)).is_err());
+    /// ```
+    pub fn shift(&self, var: &str, a: Coeff) -> Result<Polynomial, PolyError> {
+        let mut coeffs = self.coefficients_in(var)?;
+        // After step i, coeffs[i] is final: each pass adds a times the next coefficient from the top down
+        let n = coeffs.len();
+        for i in 0..n {
+            for j in (i..n.saturating_sub(1)).rev() {
+                let next = coeffs[j + 1].map_coefficients(|c| c * &a);
+                coeffs[j] += next;
+            }
+        }
+        let mut result = Polynomial::zero();
+        for (k, c) in coeffs.into_iter().enumerate() {
+            result += c * Polynomial::monomial(Coeff::one(), &[(var, (k as i64).into())]);
+        }
+        result.pending_pow = self.pending_pow;
+        Ok(result)
+    }
+
+    /// Returns the coefficients of each power of `var` from 0 upward, which are polynomials in the other variables.
+    /// Only whole, non-negative powers of `var` are allowed. The pending power is ignored.
+    fn coefficients_in(&self, var: &str) -> Result<Vec<Polynomial>, PolyError> {
+        let mut p = self.clone();
+        p.simplify();
+        let mut coeffs: Vec<Polynomial> = Vec::new();
+        for term in &p.terms {
+            let mut rest = term.clone();
+            rest.variables.retain(|v| v.name != var);
+            let k = match term.variables.iter().find(|v| v.name == var) {
+                Some(v) if !v.degree.is_integer() => {
+                    return Err(PolyError::FractionalExponent {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    })
+                }
+                Some(v) if v.degree < 0.into() => {
+                    return Err(PolyError::NegativeExponent {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    })
+                }
+                Some(v) => v.degree.to_integer() as usize,
+                None => 0,
+            };
+            if coeffs.len() <= k {
+                coeffs.resize(k + 1, Polynomial::zero());
+            }
+            coeffs[k] += Polynomial {
+                terms: vec![rest],
+                pending_pow: 1.into(),
+            };
+        }
+        Ok(coeffs)
+    }
+
+    /// Applies the pending power to the terms where it can be done: a single term is raised to it directly and a whole
+    /// power of several terms is expanded. Any other power, like the square root of `x+1`, stays pending. The result is simplified.
+    pub fn apply_pow(&mut self) {
+        if self.pending_pow != 1.into() {
+            self.simplify();
+            if self.terms.len() == 1 {
+                let powered = self.terms[0].pow(self.pending_pow); // TODO: Here, sqrt(13) becomes a ratio
+                self.terms = powered.terms;
+                self.pending_pow = powered.pending_pow;
+            } else if self.pending_pow.is_integer() && self.pending_pow >= 2.into() {
+                let exp = self.pending_pow.to_integer() as u32;
+                self.pending_pow = 1.into();
+                *self = self.powi(exp);
+            }
+        }
+        self.simplify();
+    }
+
+    /// Multiplies the polynomial by the smallest scalar such that all coefficients are integers. Returns the scalar,
+    /// or an error if it or one of the new coefficients overflows, in which case the polynomial is left unchanged.
+    pub fn make_integer(&mut self) -> Result<CoeffInt, PolyError> {
+        // Get the lcm of the denominators of the coefficients
+        let mut lcm = CoeffInt::one();
+        for term in &self.terms {
+            let denom = term.coefficient.denom();
+            lcm = CheckedMul::checked_mul(&(lcm.clone() / lcm.gcd(denom)), denom)
+                .ok_or(PolyError::CoefficientOverflow)?;
+        }
+        // Multiply each coefficient by the lcm
+        let scalar = Coeff::from_integer(lcm.clone());
+        let mut coefficients = Vec::with_capacity(self.terms.len());
+        for term in &self.terms {
+            coefficients.push(
+                term.coefficient
+                    .checked_mul(&scalar)
+                    .ok_or(PolyError::CoefficientOverflow)?,
+            );
+        }
+        for (term, coefficient) in self.terms.iter_mut().zip(coefficients) {
+            term.coefficient = coefficient;
+        }
+        Ok(lcm)
+    }
+
+    /// Renames the variable `from` to `to` in every term. The polynomial is simplified afterwards, since renaming to a variable
+    /// that is already there merges them: renaming y to x in xy gives x^2, and in xy-x^2 gives 0.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// let mut p = poly!(x y + 2 t);
+    /// p.rename_variable("y", "x");
+    /// assert_eq!(p, poly!(x^2 + 2 t));
+    /// p.rename_variable("t", "x");
+    /// assert_eq!(p.as_string(), "x^(2)+2x");
+    ///
+    /// let mut p = poly!(x y - x^2);
+    /// p.rename_variable("y", "x");
+    /// assert!(p.is_zero());
+    /// ```
+    pub fn rename_variable(&mut self, from: &str, to: &str) {
+        if from == to {
+            return;
+        }
+        for term in &mut self.terms {
+            for var in &mut term.variables {
+                if var.name == from {
+                    var.name = to.to_string();
+                }
+            }
+        }
+        self.simplify();
+    }
+
+    /// Applies `f` to the coefficient of every term and simplifies the result, so terms that become zero are dropped.
+    pub fn map_coefficients(&self, f: impl Fn(Coeff) -> Coeff) -> Polynomial {
+        let mut p = self.clone();
+        for term in &mut p.terms {
+            term.coefficient = f(term.coefficient.clone());
+        }
+        p.simplify();
+        p
+    }
+
+    /// Like `map_coefficients`, but stops at the first coefficient for which `f` fails and returns its error.
+    pub fn try_map_coefficients<E>(
+        &self,
+        f: impl Fn(Coeff) -> Result<Coeff, E>,
+    ) -> Result<Polynomial, E> {
+        let mut p = self.clone();
+        for term in &mut p.terms {
+            term.coefficient = f(term.coefficient.clone())?;
+        }
+        p.simplify();
+        Ok(p)
+    }
+
+    /// Returns the content of the polynomial: the greatest common divisor of its coefficients, which for fractions is the gcd
+    /// of the numerators over the lcm of the denominators. The content is never negative, and it is 0 for the zero polynomial.
+    /// A polynomial raised to a pending power has content 1, as its coefficients are not known until it is expanded.
+    pub fn content(&self) -> Coeff {
+        let mut p = self.clone();
+        p.apply_pow();
+        if p.pending_pow != 1.into() {
+            return Coeff::one();
+        }
+        let mut numer = CoeffInt::zero();
+        let mut denom = CoeffInt::one();
+        for term in &p.terms {
+            numer = numer.gcd(term.coefficient.numer());
+            let d = term.coefficient.denom();
+            denom = CheckedMul::checked_mul(&(denom.clone() / denom.gcd(d)), d)
+                .unwrap_or_else(|| overflow());
+        }
+        Coeff::new(numer, denom)
+    }
+
+    /// Returns the content of the polynomial and its primitive part, the polynomial divided by the content,
+    /// which has integer coefficients without a common factor. The zero polynomial is returned unchanged.
+    pub fn primitive_part(&self) -> (Coeff, Polynomial) {
+        let content = self.content();
+        if content.is_zero() {
+            return (content, self.clone());
+        }
+        let primitive =
+            self.map_coefficients(|c| c.checked_div(&content).unwrap_or_else(|| overflow()));
+        (content, primitive)
+    }
+
+    /// Finds the greatest common divisor of the coefficients of the terms in a single-variable polynomial with integer coefficients. Returns the gcd and the polynomial with the gcd factored out.
+    pub fn factor(&mut self) -> (Term, Polynomial) {
+        if self.pending_pow != 1.into() {
+            return (Term::constant(Coeff::one()), self.clone()); // Only works for degree 1 polynomials
+        }
+        if self.is_zero() {
+            return (Term::constant(Coeff::zero()), self.clone()); // The gcd of no coefficients at all is 0
+        }
+        // The gcd of the coefficients is the content
+        let (content, p) = self.primitive_part();
+        let mut factored_out = Term::constant(content);
+
+        // Check the name of the variable that appears in all terms
+        let mut seen_vars = vec![];
+        for var in self.iter().next().unwrap().iter_vars() {
+            seen_vars.push(var.name.clone());
+        }
+        for term in self.iter() {
+            let mut curr_vars = vec![];
+            for var in term.iter_vars() {
+                curr_vars.push(var.name.clone());
+            }
+            seen_vars = seen_vars
+                .iter()
+                .map(|x| x.clone())
+                .filter(|x| curr_vars.contains(x))
+                .collect();
+
+            if seen_vars.len() == 0 {
+                return (factored_out, p); // No common variable, only the content is factored out
+            }
+        }
+        let var_name = seen_vars[0].clone(); // Always the first element
+                                             // TODO: Maybe add an option to this function to specify the variable to factor out
+
+        // Find the smallest power of the variable that appears in all terms
+        let mut min_degree: Option<Rational64> = None;
+        for term in &p {
+            for var in term.iter_vars() {
+                if var.name == var_name && var.degree > 0.into() {
+                    min_degree = Some(match min_degree {
+                        Some(d) if d < var.degree => d,
+                        _ => var.degree,
+                    });
+                }
+            }
+        }
+        let min_degree = min_degree.unwrap_or_else(|| 0.into());
+
+        // Factor out the smallest power too
+        let mut factored = p * Polynomial::monomial(Coeff::one(), &[(&var_name, -min_degree)]);
+        factored_out.variables.push(Variable {
+            name: var_name,
+            degree: min_degree,
+        });
+
+        factored.simplify();
+
+        return (factored_out, factored);
+    }
+
+    /// Returns true if the polynomial has no variables once simplified, like 5 or x-x+2.
+    pub fn is_constant(&self) -> bool {
+        let mut p = self.clone();
+        p.simplify();
+        p.terms.iter().all(|t| t.variables.is_empty())
+    }
+
+    /// Returns the name of the only variable in the polynomial once simplified, or `None` if there are none or several.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// assert_eq!(poly!(x^2 + 2 x y - 2 y x).is_univariate(), Some("x".to_string()));
+    /// assert_eq!(poly!(x + y).is_univariate(), None);
+    /// assert_eq!(poly!(x - x + 2).is_univariate(), None);
+    /// assert!(poly!(x - x + 2).is_constant());
+    /// assert!(!poly!(x^2).is_constant());
+    /// ```
+    pub fn is_univariate(&self) -> Option<String> {
+        let mut p = self.clone();
+        p.simplify();
+        match p.variables().as_slice() {
+            [var] => Some(var.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the total degree shared by every term if the polynomial is homogeneous, like 2 for x^2+xy+y^2, or `None` if
+    /// the terms have different total degrees. The zero polynomial has no degree, so it gives `None` too.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// assert_eq!(poly!(x^2 + x y + y^2).is_homogeneous(), Some(2.into()));
+    /// assert_eq!(poly!(x^2 + x).is_homogeneous(), None);
+    /// assert_eq!(poly!(x^2 + x).homogenize("z"), poly!(x^2 + x z));
+    /// assert_eq!(poly!(x^3 y + x + 1).homogenize("z").is_homogeneous(), Some(4.into()));
+    /// assert_eq!(poly!(x^2 y).leading_term().total_degree(), 3.into());
+    /// ```
+    pub fn is_homogeneous(&self) -> Option<Rational64> {
+        let mut p = self.clone();
+        p.apply_pow();
+        let mut degrees = p
+            .terms
+            .iter()
+            .filter(|t| !t.coefficient.is_zero())
+            .map(|t| t.total_degree());
+        let first = degrees.next()?;
+        if degrees.all(|d| d == first) {
+            Some(first * p.pending_pow)
+        } else {
+            None
+        }
+    }
+
+    /// Multiplies each term by the power of `new_var` that brings it up to the highest total degree, so the result is
+    /// homogeneous, like x^2+x+1 -> x^2+xz+z^2. Setting `new_var` to 1 gives back the polynomial if it did not contain `new_var`.
+    pub fn homogenize(&self, new_var: &str) -> Polynomial {
+        let mut p = self.clone();
+        p.apply_pow();
+        let degree = p
+            .terms
+            .iter()
+            .filter(|t| !t.coefficient.is_zero())
+            .map(|t| t.total_degree())
+            .max()
+            .unwrap_or(0.into());
+        for term in &mut p.terms {
+            let missing = degree - term.total_degree();
+            if missing != 0.into() {
+                term.variables.push(Variable {
+                    name: new_var.to_string(),
+                    degree: missing,
+                });
+            }
+        }
+        p.simplify();
+        p
+    }
+
+    /// Returns the names of the variables in the simplified polynomial, in alphabetical order.
+    /// Variables that cancel out, like x in x-x+1, are not included.
+    pub fn vars(&self) -> BTreeSet<String> {
+        let mut p = self.clone();
+        p.simplify();
+        p.terms
+            .iter()
+            .filter(|t| !t.coefficient.is_zero())
+            .flat_map(|t| t.variables.iter())
+            .filter(|v| v.degree != 0.into())
+            .map(|v| v.name.clone())
+            .collect()
+    }
+
+    /// Returns the names of the variables in the polynomial, in order of appearance and without repetitions.
+    pub fn variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for term in &self.terms {
+            for var in &term.variables {
+                if !names.contains(&var.name) {
+                    names.push(var.name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// Divides a single-variable polynomial by another one using long division. Returns the quotient and the remainder.
+    /// Dividing by the zero polynomial gives a zero quotient and the dividend as the remainder.
+    pub fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        let mut quotient = Polynomial {
+            terms: vec![],
+            pending_pow: 1.into(),
+        };
+        let mut remainder = self.clone();
+        remainder.simplify();
+        let mut divisor = divisor.clone();
+        divisor.simplify();
+        if !divisor.is_zero() {
+            let lead = divisor.leading_term();
+            while remainder.degree() >= divisor.degree() {
+                let t = remainder.leading_term() / lead.clone();
+                quotient += &t;
+                remainder -= divisor.clone() * t;
+            }
+        }
+        quotient.simplify();
+        (quotient, remainder)
+    }
+
+    /// Divides the polynomial by several polynomials at once with respect to a monomial order, returning a quotient for each
+    /// divisor and a remainder none of whose terms is divisible by the leading term of a divisor, so
+    /// self = q1 f1 + q2 f2 + ... + r. The leading term of what is left is divided by the first divisor whose leading term
+    /// divides it, so the result depends on the order of the divisors. Zero divisors and divisors with negative powers
+    /// are not used, and get a zero quotient.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::MonomialOrder;
+    ///
+    /// // The remainder depends on the order of the divisors
+    /// let f = poly!(x^2 y + x y^2 + y^2);
+    /// let (q, r) = f.reduce(&[poly!(x y - 1), poly!(y^2 - 1)], MonomialOrder::Lex);
+    /// assert_eq!((q, r), (vec![poly!(x + y), poly!(1)], poly!(x + y + 1)));
+    /// let (q, r) = f.reduce(&[poly!(y^2 - 1), poly!(x y - 1)], MonomialOrder::Lex);
+    /// assert_eq!((q, r), (vec![poly!(x + 1), poly!(x)], poly!(2 x + 1)));
+    /// ```
+    pub fn reduce(
+        &self,
+        divisors: &[Polynomial],
+        order: MonomialOrder,
+    ) -> (Vec<Polynomial>, Polynomial) {
+        let mut quotients = vec![Polynomial::zero(); divisors.len()];
+        let mut remainder = Polynomial::zero();
+        let mut p = self.clone();
+        p.simplify();
+        let divisors: Vec<(Polynomial, Term)> = divisors
+            .iter()
+            .map(|f| {
+                let mut f = f.clone();
+                f.simplify();
+                let lead = f.leading_term_by(order);
+                (f, lead)
+            })
+            .collect();
+        let usable = |f: &Polynomial| {
+            !f.is_zero()
+                && f.terms
+                    .iter()
+                    .all(|t| t.variables.iter().all(|v| v.degree >= 0.into()))
+        };
+        while !p.is_zero() {
+            let lead = p.leading_term_by(order);
+            match divisors
+                .iter()
+                .position(|(f, f_lead)| usable(f) && f_lead.divides(&lead))
+            {
+                Some(i) => {
+                    let t = lead / divisors[i].1.clone();
+                    quotients[i] += &t;
+                    p -= divisors[i].0.clone() * t;
+                }
+                None => {
+                    let lead = Polynomial {
+                        terms: vec![lead],
+                        pending_pow: 1.into(),
+                    };
+                    remainder += &lead;
+                    p -= lead;
+                }
+            }
+        }
+        (quotients, remainder)
+    }
+
+    /// Computes the greatest common divisor of two polynomials with the Euclidean algorithm.
+    /// The result has integer coefficients without a common factor and a positive leading coefficient.
+    /// If the polynomials have more than one variable between them, only their common monomial factor is found.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// assert_eq!(poly!(x^2 + 2 x + 1).gcd(&poly!(x^2 - 1)), poly!(x + 1));
+    /// assert_eq!(poly!(1/2 x^2 - 1/2).gcd(&poly!(3 x - 3)), poly!(x - 1));
+    /// assert_eq!(poly!(x^2 + 1).gcd(&poly!(x - 1)), poly!(1));
+    /// assert_eq!(poly!(0).gcd(&poly!(-2 x + 4)), poly!(x - 2));
+    /// ```
+    pub fn gcd(&self, other: &Polynomial) -> Polynomial {
+        let mut a = self.clone();
+        a.simplify();
+        let mut b = other.clone();
+        b.simplify();
+        let mut names = a.variables();
+        for name in b.variables() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        let whole_powers = |p: &Polynomial| {
+            p.pending_pow == 1.into()
+                && p.terms.iter().all(|t| {
+                    t.variables
+                        .iter()
+                        .all(|v| v.degree.is_integer() && v.degree > 0.into())
+                })
+        };
+        let one = Polynomial {
+            terms: vec![Term {
+                coefficient: Coeff::one(),
+                variables: vec![],
+            }],
+            pending_pow: 1.into(),
+        };
+        if !whole_powers(&a) || !whole_powers(&b) {
+            return one;
+        }
+        if names.len() > 1 {
+            if a.is_zero() {
+                return b.primitive();
+            } else if b.is_zero() {
+                return a.primitive();
+            }
+            // The lowest power of each variable that appears in every term
+            let mut common = one.terms[0].clone();
+            for name in names {
+                let min_degree = a
+                    .terms
+                    .iter()
+                    .chain(b.terms.iter())
+                    .map(|t| {
+                        t.variables
+                            .iter()
+                            .find(|v| v.name == name)
+                            .map(|v| v.degree)
+                            .unwrap_or(0.into())
+                    })
+                    .min()
+                    .unwrap_or(0.into());
+                if min_degree > 0.into() {
+                    common.variables.push(Variable {
+                        name,
+                        degree: min_degree,
+                    });
+                }
+            }
+            common.sort_vars();
+            return Polynomial {
+                terms: vec![common],
+                pending_pow: 1.into(),
+            };
+        }
+        while !b.is_zero() {
+            let r = a % b.clone();
+            a = b;
+            b = r;
+        }
+        a.primitive()
+    }
+
+    /// Computes the greatest common divisor `g` of two single-variable polynomials along with `s` and `t` such that `s*self + t*other = g`.
+    /// `g` is normalized like in `gcd`, and `s` and `t` are scaled to match.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// let pairs = [
+    ///     (poly!(x^2 - 1), poly!(x^2 + 2 x + 1)),
+    ///     (poly!(x^3 - 2 x + 5), poly!(x - 2)),
+    ///     (poly!(1/2 x^4 - 3/4 x^2), poly!(2/3 x^3 + 1/5 x)),
+    ///     (poly!(x^2 + 1), poly!(x^2 + 1)),
+    ///     (poly!(3 x - 1/7), poly!(4)),
+    /// ];
+    /// for (a, b) in pairs {
+    ///     let (g, s, t) = a.extended_gcd(&b);
+    ///     assert_eq!(s * a.clone() + t * b.clone(), g);
+    ///     assert_eq!(g, a.gcd(&b));
+    /// }
+    /// ```
+    pub fn extended_gcd(&self, other: &Polynomial) -> (Polynomial, Polynomial, Polynomial) {
+        let constant = |c: i64| Polynomial {
+            terms: vec![Term {
+                coefficient: coeff(c),
+                variables: vec![],
+            }],
+            pending_pow: 1.into(),
+        };
+        let (mut old_r, mut r) = (self.clone(), other.clone());
+        let (mut old_s, mut s) = (constant(1), constant(0));
+        let (mut old_t, mut t) = (constant(0), constant(1));
+        old_r.simplify();
+        r.simplify();
+        while !r.is_zero() {
+            let (q, rem) = old_r.div_rem(&r);
+            old_r = std::mem::replace(&mut r, rem);
+            let next_s = old_s - q.clone() * s.clone();
+            old_s = std::mem::replace(&mut s, next_s);
+            let next_t = old_t - q * t.clone();
+            old_t = std::mem::replace(&mut t, next_t);
+        }
+        if old_r.is_zero() {
+            return (old_r, old_s, old_t);
+        }
+        let g = old_r.clone().primitive();
+        let scale = Polynomial {
+            terms: vec![Term {
+                coefficient: g.leading_term().coefficient / old_r.leading_term().coefficient,
+                variables: vec![],
+            }],
+            pending_pow: 1.into(),
+        };
+        (g, old_s * scale.clone(), old_t * scale)
+    }
+
+    /// Reduces the polynomial modulo the prime `p`, so every coefficient is in [0, p). A fraction a/b becomes a times the
+    /// inverse of b modulo p, like 1/2 = 3 modulo 5, and is an error if p divides b. Non-prime moduli are rejected, since
+    /// the coefficients would not form a field.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::PolyError;
+    ///
+    /// assert_eq!(poly!(x + 1).pow_mod(5, 5).unwrap(), poly!(x^5 + 1));
+    /// assert_eq!(poly!(x^2 - 7 x + 1/2).mod_p(5).unwrap(), poly!(x^2 + 3 x + 3));
+    /// assert_eq!(poly!(4 x + 3).add_mod(&poly!(2 x + 4), 5).unwrap(), poly!(x + 2));
+    /// assert_eq!(poly!(2 x + 1).mul_mod(&poly!(3 x + 1), 5).unwrap(), poly!(x^2 + 1));
+    /// assert_eq!(poly!(x^2 - 1).gcd_mod(&poly!(x^2 + 3 x + 2), 5).unwrap(), poly!(x + 1));
+    /// assert!(matches!(poly!(x).mod_p(6), Err(PolyError::NotPrime { .. })));
+    /// assert!(matches!(poly!(1/5 x).mod_p(5), Err(PolyError::NoInverseMod { .. })));
+    /// ```
+    pub fn mod_p(&self, p: i64) -> Result<Polynomial, PolyError> {
+        if !is_prime(p) {
+            return Err(PolyError::NotPrime { modulus: p });
+        }
+        let mut q = self.clone();
+        q.apply_pow();
+        if q.pending_pow != 1.into() {
+            return Err(PolyError::FractionalExponent {
+                var: format!("({})", q.as_string()),
+                degree: q.pending_pow,
+            });
+        }
+        let modulus = coeff(p).to_integer();
+        for term in &mut q.terms {
+            let denominator = term.coefficient.denom().mod_floor(&modulus);
+            if denominator.is_zero() {
+                return Err(PolyError::NoInverseMod {
+                    denominator: term.coefficient.denom().clone(),
+                    modulus: p,
+                });
+            }
+            let numerator = term.coefficient.numer().mod_floor(&modulus);
+            term.coefficient = Coeff::from_integer(
+                (numerator * inverse_mod(&denominator, &modulus)).mod_floor(&modulus),
+            );
+        }
+        q.simplify();
+        Ok(q)
+    }
+
+    /// Adds two polynomials over GF(p), reducing the sum modulo the prime `p`.
+    pub fn add_mod(&self, other: &Polynomial, p: i64) -> Result<Polynomial, PolyError> {
+        self.mod_p(p)?.checked_add(&other.mod_p(p)?)?.mod_p(p)
+    }
+
+    /// Multiplies two polynomials over GF(p), reducing the product modulo the prime `p`.
+    pub fn mul_mod(&self, other: &Polynomial, p: i64) -> Result<Polynomial, PolyError> {
+        self.mod_p(p)?.checked_mul(&other.mod_p(p)?)?.mod_p(p)
+    }
+
+    /// Raises the polynomial to a whole power over GF(p) by squaring, reducing modulo the prime `p` after every product.
+    pub fn pow_mod(&self, mut exp: u32, p: i64) -> Result<Polynomial, PolyError> {
+        let mut base = self.mod_p(p)?;
+        let mut result = Polynomial::one().mod_p(p)?;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul_mod(&base, p)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul_mod(&base, p)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Computes the greatest common divisor of two single-variable polynomials over GF(p) with the Euclidean algorithm.
+    /// The result is monic, or zero if both polynomials are zero modulo `p`.
+    pub fn gcd_mod(&self, other: &Polynomial, p: i64) -> Result<Polynomial, PolyError> {
+        let (a, b) = (self.mod_p(p)?, other.mod_p(p)?);
+        let mut names = a.variables();
+        for name in b.variables() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        if names.len() > 1 {
+            return Err(PolyError::NotUnivariate {
+                var: names[0].clone(),
+                other: names[1].clone(),
+            });
+        }
+        let var = names.pop().unwrap_or_else(|| "x".to_string());
+        let integers = |p: &Polynomial| -> Result<Vec<CoeffInt>, PolyError> {
+            Ok(p.to_coeff_vec(&var)?
+                .iter()
+                .map(|c| c.to_integer())
+                .collect())
+        };
+        let modulus = coeff(p).to_integer();
+        let (mut a, mut b) = (integers(&a)?, integers(&b)?);
+        while !b.is_empty() {
+            let r = rem_mod(a, &b, &modulus);
+            a = b;
+            b = r;
+        }
+        if let Some(lead) = a.last() {
+            let inverse = inverse_mod(lead, &modulus);
+            for c in &mut a {
+                *c = (c.clone() * inverse.clone()).mod_floor(&modulus);
+            }
+        }
+        let coeffs: Vec<Coeff> = a.into_iter().map(Coeff::from_integer).collect();
+        Ok(Polynomial::from_coeff_vec(&var, &coeffs))
+    }
+
+    /// Computes the least common multiple of two polynomials as their product divided by their gcd.
+    /// The least common multiple of zero and any polynomial is zero.
+    ///
+    /// ```
+    /// use sym_tfg::{poly, ratio};
+    ///
+    /// // Up to a constant factor
+    /// let lcm = poly!(x + 1).lcm(&poly!(x^2 - 1));
+    /// assert_eq!(lcm.clone() * lcm.leading_term().coefficient.recip(), poly!(x^2 - 1));
+    /// assert_eq!(poly!(0).lcm(&poly!(x)), poly!(0));
+    ///
+    /// // Adding over the lcm of the denominators keeps the sum from growing to degree 3
+    /// let sum = ratio!(1; x + 1) + ratio!(1; x^2 - 1);
+    /// assert_eq!(sum, ratio!(x; x^2 - 1));
+    /// assert_eq!(sum.denominator.degree(), Some(2.into()));
+    /// ```
+    pub fn lcm(&self, other: &Polynomial) -> Polynomial {
+        let mut product = self.clone() * other.clone();
+        if self.is_zero() || other.is_zero() {
+            product.simplify();
+            return product;
+        }
+        let (quotient, remainder) = other.div_rem(&self.gcd(other));
+        if remainder.is_zero() {
+            self.clone() * quotient
+        } else {
+            product
+        }
+    }
+
+    /// Scales the polynomial so that its coefficients are integers without a common factor and the leading one is positive.
+    fn primitive(self) -> Polynomial {
+        let (_, p) = self.primitive_part();
+        if p.leading_term().coefficient.is_negative() {
+            return -p;
+        }
+        p
+    }
+
+    /// Returns the square root of the polynomial. If the polynomial is not a perfect square,
+    /// the root is kept symbolic as the polynomial raised to 1/2.
+    pub fn sqrt(&self) -> Polynomial {
+        let mut p = self.clone();
+        p.apply_pow();
+        let half = Rational64::new(1, 2);
+        let symbolic = Polynomial {
+            terms: p.terms.clone(),
+            pending_pow: p.pending_pow * half,
+        };
+        if p.pending_pow != 1.into() {
+            return symbolic;
+        }
+        if p.terms.len() == 1 {
+            return p.terms[0].pow(half);
+        }
+
+        // Take the root of the leading term, then find the rest of the root one term at a time
+        // Terms are sorted with the highest degrees first after simplifying
+        let first = p.terms[0].pow(half);
+        if first.pending_pow != 1.into()
+            || first.terms[0]
+                .variables
+                .iter()
+                .any(|v| !v.degree.is_integer())
+        {
+            return symbolic;
+        }
+        let lead = first.terms[0].clone();
+        let mut root = first;
+        let steps = (p.degree().unwrap_or(0.into()) / 2).to_integer() + 1;
+        for _ in 0..steps {
+            let remainder = p.clone() - root.clone() * root.clone();
+            if remainder.is_zero() {
+                return root;
+            }
+            let next = remainder.terms[0].clone()
+                / Term {
+                    coefficient: &lead.coefficient * coeff(2),
+                    variables: lead.variables.clone(),
+                };
+            if next
+                .terms
+                .iter()
+                .flat_map(|t| t.variables.iter())
+                .any(|v| v.degree < 0.into())
+            {
+                break;
+            }
+            root += next;
+        }
+        symbolic
+    }
+
+    /// Differentiates the polynomial with respect to the variable `var`, term by term.
+    /// Terms without `var` vanish, and fractional or negative powers follow the same rule as any other power.
+    pub fn derivative(&self, var: &str) -> Polynomial {
+        let mut p = self.clone();
+        p.simplify();
+        let mut result = Polynomial {
+            terms: vec![],
+            pending_pow: 1.into(),
+        };
+        for term in &p.terms {
+            if let Some(v) = term.variables.iter().find(|v| v.name == var) {
+                let mut new_term = term.clone();
+                new_term.coefficient = new_term
+                    .coefficient
+                    .try_mul(&degree_coeff(v.degree))
+                    .unwrap_or_else(|| overflow());
+                for new_var in &mut new_term.variables {
+                    if new_var.name == var {
+                        new_var.degree = new_var
+                            .degree
+                            .try_add(&(-1).into())
+                            .unwrap_or_else(|| overflow());
+                    }
+                }
+                new_term.factor();
+                result.terms.push(new_term);
+            }
+        }
+        result.simplify();
+        result
+    }
+
+    /// Differentiates the polynomial `n` times with respect to the variable `var`. An order of 0 returns the polynomial unchanged.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// let p = poly!(x^3 + x y);
+    /// assert_eq!(p.nth_derivative("x", 3), poly!(6));
+    /// assert_eq!(p.nth_derivative("x", 0).as_string(), p.as_string());
+    /// assert_eq!(p.nth_derivative("x", u32::MAX), poly!(0));
+    /// ```
+    pub fn nth_derivative(&self, var: &str, n: u32) -> Polynomial {
+        let whole_powers = self.terms.iter().all(|t| {
+            t.variables
+                .iter()
+                .filter(|v| v.name == var)
+                .all(|v| v.degree.is_integer() && v.degree > 0.into())
+        });
+        if whole_powers && Rational64::from(n as i64) > self.degree_in(var) {
+            // Every term vanishes before reaching the last derivative
+            return Polynomial {
+                terms: vec![Term {
+                    coefficient: Coeff::zero(),
+                    variables: vec![],
+                }],
+                pending_pow: 1.into(),
+            };
+        }
+        let mut result = self.clone();
+        for _ in 0..n {
+            result = result.derivative(var);
+        }
+        result
+    }
+
+    /// Integrates the polynomial with respect to the variable `var`, term by term, without a constant of integration.
+    /// Fails if `var` appears with a power of -1, since its integral is a logarithm.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::PolyError;
+    ///
+    /// for p in [poly!(3 x^2 - 1/2 x + 4), poly!(x^-2 y + x^(1/2)), poly!(y)] {
+    ///     assert_eq!(p.integral("x").unwrap().derivative("x"), p);
+    /// }
+    /// assert!(matches!(poly!(x + x^-1).integral("x"), Err(PolyError::LogarithmicIntegral { .. })));
+    /// ```
+    pub fn integral(&self, var: &str) -> Result<Polynomial, PolyError> {
+        let mut p = self.clone();
+        p.simplify();
+        let mut result = Polynomial {
+            terms: vec![],
+            pending_pow: 1.into(),
+        };
+        for term in &p.terms {
+            let mut new_term = term.clone();
+            match new_term.variables.iter_mut().find(|v| v.name == var) {
+                Some(v) if v.degree == (-1).into() => {
+                    return Err(PolyError::LogarithmicIntegral {
+                        var: var.to_string(),
+                    });
+                }
+                Some(v) => {
+                    v.degree = v
+                        .degree
+                        .try_add(&1.into())
+                        .ok_or(PolyError::CoefficientOverflow)?;
+                    new_term.coefficient = new_term
+                        .coefficient
+                        .checked_div(&degree_coeff(v.degree))
+                        .ok_or(PolyError::CoefficientOverflow)?;
+                }
+                None => new_term.variables.push(Variable {
+                    name: var.to_string(),
+                    degree: 1.into(),
+                }),
+            }
+            new_term.sort_vars();
+            result.terms.push(new_term);
+        }
+        result.simplify();
+        Ok(result)
+    }
+
+    /// Integrates the polynomial with respect to `var` from `a` to `b`, using exact rational arithmetic.
+    /// The result is a constant unless other variables remain. Negative powers of `var` are an error if the interval
+    /// contains 0, since the integral is improper there, like the one of x^-2 from -1 to 1, which diverges.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::{coeff, PolyError};
+    ///
+    /// assert_eq!(poly!(x^2).definite_integral("x", coeff(0), coeff(1)).unwrap(), poly!(1/3));
+    /// assert_eq!(poly!(x y + 1).definite_integral("x", coeff(0), coeff(2)).unwrap(), poly!(2 y + 2));
+    /// assert_eq!(poly!(x^-2).definite_integral("x", coeff(1), coeff(2)).unwrap(), poly!(1/2));
+    /// let improper = poly!(x^-2).definite_integral("x", coeff(-1), coeff(1));
+    /// assert!(matches!(improper, Err(PolyError::ImproperIntegral { .. })));
+    /// ```
+    pub fn definite_integral(
+        &self,
+        var: &str,
+        a: Coeff,
+        b: Coeff,
+    ) -> Result<Polynomial, PolyError> {
+        let zero = Coeff::zero();
+        if a.clone().min(b.clone()) <= zero && zero <= a.clone().max(b.clone()) {
+            let mut p = self.clone();
+            p.simplify();
+            let negative = p
+                .terms
+                .iter()
+                .filter(|t| !t.coefficient.is_zero())
+                .flat_map(|t| &t.variables)
+                .find(|v| v.name == var && v.degree < 0.into());
+            if let Some(v) = negative {
+                return Err(PolyError::ImproperIntegral {
+                    var: var.to_string(),
+                    degree: v.degree,
+                });
+            }
+        }
+        let antiderivative = self.integral(var)?;
+        Ok(antiderivative.at(var, b)? - antiderivative.at(var, a)?)
+    }
+
+    /// Replaces the variable `var` with an exact rational value.
+    fn at(&self, var: &str, value: Coeff) -> Result<Polynomial, PolyError> {
+        let mut result = self.clone();
+        result.evaluate(&[(var.to_string(), value)])?;
+        result.simplify();
+        Ok(result)
+    }
+
+    /// Returns the name of the first variable in the polynomial.
+    pub fn first_var(&self) -> Option<String> {
+        if self.terms.len() == 0 || self.terms[0].variables.len() == 0 {
+            return None;
+        } else {
+            return Some(self.terms[0].variables[0].name.clone());
+        }
+    }
+
+    /// Find symbolic coefficient by degree.
+    pub fn find_sym_coeff(&self, var: &str, degree: Rational64) -> (Term, Term) {
+        let zero = Term {
+            coefficient: Coeff::zero(),
+            variables: vec![],
+        };
+        let term = self
+            .terms
+            .iter()
+            .find(|t| {
+                t.variables
+                    .iter()
+                    .any(|v| v.name == var && v.degree == degree)
+            })
+            // .unwrap_or_else(|| panic!("No term with {}^{}", var, degree))
+            .unwrap_or_else(|| &zero)
+            .clone();
+        let sym_coeff = Term {
+            coefficient: term.coefficient.clone(),
+            variables: term
+                .variables
+                .iter()
+                .filter(|v| v.name != var)
+                .cloned()
+                .collect(),
+        };
+        return (term, sym_coeff);
+    }
+
+    /// Returns the coefficient of `var^k`: the sum of every term in which `var` has exactly the power `k`, with `var` removed.
+    /// Terms without `var` count as having power 0. The coefficient may contain other variables, and is zero if no term matches.
+    pub fn coefficient(&self, var: &str, k: Rational64) -> Polynomial {
+        let mut p = self.clone();
+        p.simplify();
+        let mut result = Polynomial {
+            terms: p
+                .terms
+                .into_iter()
+                .filter(|t| !t.coefficient.is_zero())
+                .filter(|t| {
+                    t.variables
+                        .iter()
+                        .find(|v| v.name == var)
+                        .map_or(Rational64::from(0), |v| v.degree)
+                        == k
+                })
+                .map(|mut t| {
+                    t.variables.retain(|v| v.name != var);
+                    t
+                })
+                .collect(),
+            pending_pow: 1.into(),
+        };
+        result.simplify();
+        result
+    }
+
+    /// Returns the coefficients of a polynomial in `var` alone, from degree 0 upward.
+    /// Every exponent must be a non-negative integer. The zero polynomial gives an empty vector.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::{coeff, PolyError, Polynomial};
+    ///
+    /// let coeffs = poly!(2 x^3 - 1/2 x + 5).to_coeff_vec("x").unwrap();
+    /// assert_eq!(coeffs, [coeff(5), coeff(-1) / coeff(2), coeff(0), coeff(2)]);
+    /// assert_eq!(Polynomial::from_coeff_vec("x", &coeffs), poly!(2 x^3 - 1/2 x + 5));
+    /// assert!(poly!(0).to_coeff_vec("x").unwrap().is_empty());
+    ///
+    /// assert!(matches!(poly!(x y).to_coeff_vec("x"), Err(PolyError::NotUnivariate { .. })));
+    /// assert!(poly!(x^(1/2)).to_coeff_vec("x").is_err());
+    /// assert!(poly!(x^-1).to_coeff_vec("x").is_err());
+    /// ```
+    pub fn to_coeff_vec(&self, var: &str) -> Result<Vec<Coeff>, PolyError> {
+        let mut p = self.clone();
+        p.simplify();
+        if p.pending_pow != 1.into() {
+            return Err(PolyError::FractionalExponent {
+                var: format!("({})", p.as_string()),
+                degree: p.pending_pow,
+            });
+        }
+        let mut coeffs: Vec<Coeff> = Vec::new();
+        for term in p.terms.iter().filter(|t| !t.coefficient.is_zero()) {
+            let mut degree = 0;
+            for v in &term.variables {
+                if v.name != var {
+                    return Err(PolyError::NotUnivariate {
+                        var: var.to_string(),
+                        other: v.name.clone(),
+                    });
+                }
+                if !v.degree.is_integer() {
+                    return Err(PolyError::FractionalExponent {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    });
+                }
+                if v.degree < 0.into() {
+                    return Err(PolyError::NegativeExponent {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    });
+                }
+                degree = v.degree.to_integer() as usize;
+            }
+            if coeffs.len() <= degree {
+                coeffs.resize(degree + 1, Coeff::zero());
+            }
+            coeffs[degree] += &term.coefficient;
+        }
+        Ok(coeffs)
+    }
+
+    /// Builds a polynomial in `var` from its coefficients, from degree 0 upward. This is the inverse of `to_coeff_vec`.
+    pub fn from_coeff_vec(var: &str, coeffs: &[Coeff]) -> Polynomial {
+        let mut p = Polynomial {
+            terms: coeffs
+                .iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, c)| !c.is_zero())
+                .map(|(i, c)| Term {
+                    coefficient: c.clone(),
+                    variables: vec![Variable {
+                        name: var.to_string(),
+                        degree: (i as i64).into(),
+                    }],
+                })
+                .collect(),
+            pending_pow: 1.into(),
+        };
+        p.simplify();
+        p
+    }
+
+    /// Returns the reciprocal polynomial x^n p(1/x) of a polynomial of degree n in `var`, which has its coefficients in reverse order.
+    /// Its roots are the reciprocals of the nonzero roots of p. A constant term of zero becomes a zero leading coefficient and is dropped,
+    /// so reversing twice gives back p divided by the highest power of `var` that divides it, like x^2+x -> x+1 -> x+1.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// let p = poly!(2 x^3 - x + 5);
+    /// assert_eq!(p.reversed("x").unwrap(), poly!(5 x^3 - x^2 + 2));
+    /// assert_eq!(p.reversed("x").unwrap().reversed("x").unwrap(), p);
+    ///
+    /// // The zero constant term is dropped on the way
+    /// assert_eq!(poly!(x^2 + x).reversed("x").unwrap(), poly!(x + 1));
+    /// assert_eq!(poly!(x^2 + x).reversed("x").unwrap().reversed("x").unwrap(), poly!(x + 1));
+    ///
+    /// assert!(poly!(x y).reversed("x").is_err());
+    /// assert!(poly!(x^(1/2)).reversed("x").is_err());
+    /// assert!(poly!(x^-1).reversed("x").is_err());
+    /// ```
+    pub fn reversed(&self, var: &str) -> Result<Polynomial, PolyError> {
+        let mut coeffs = self.to_coeff_vec(var)?;
+        coeffs.reverse();
+        Ok(Polynomial::from_coeff_vec(var, &coeffs))
+    }
+
+    /// Splits the polynomial into the terms with even powers of `var` and those with odd powers, so p = even + odd
+    /// and p(-x) = even - odd. Terms without `var` are even. Fractional powers, of `var` or of the whole polynomial, are neither and give an error.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// let p = poly!(x^4 - 3 x^3 y + x^2 + 1/2 x + y);
+    /// let (even, odd) = p.even_odd_parts("x").unwrap();
+    /// assert_eq!(even, poly!(x^4 + x^2 + y));
+    /// assert_eq!(odd, poly!(-3 x^3 y + 1/2 x));
+    /// assert_eq!(even.clone() + odd.clone(), p);
+    /// assert_eq!(p.substitute("x", &poly!(-x)).unwrap(), even - odd);
+    /// assert!(poly!(x^(1/2) + x).even_odd_parts("x").is_err());
+    /// ```
+    pub fn even_odd_parts(&self, var: &str) -> Result<(Polynomial, Polynomial), PolyError> {
+        let mut p = self.clone();
+        p.apply_pow();
+        if p.pending_pow != 1.into() {
+            return Err(PolyError::FractionalExponent {
+                var: format!("({})", p.as_string()),
+                degree: p.pending_pow,
+            });
+        }
+        let mut even = Polynomial::zero();
+        let mut odd = Polynomial::zero();
+        for term in p.terms {
+            let degree = term
+                .variables
+                .iter()
+                .find(|v| v.name == var)
+                .map_or(Rational64::from(0), |v| v.degree);
+            if !degree.is_integer() {
+                return Err(PolyError::FractionalExponent {
+                    var: var.to_string(),
+                    degree,
+                });
+            }
+            let part = if degree.to_integer() % 2 == 0 {
+                &mut even
+            } else {
+                &mut odd
+            };
+            part.terms.push(term);
+        }
+        even.simplify();
+        odd.simplify();
+        Ok((even, odd))
+    }
+
+    /// Returns the resultant of two polynomials in `var`, which is 0 exactly when they have a common root.
+    /// It is computed with the Euclidean remainder sequence of their coefficients, using
+    /// res(a, b) = (-1)^(deg a deg b) lc(b)^(deg a - deg r) res(b, r) for the remainder r of a divided by b.
+    /// Both polynomials must be in `var` alone with whole, non-negative powers, so coefficients with other variables
+    /// give a `NotUnivariate` error. The resultant with the zero polynomial is 0. It is also the `determinant` of the
+    /// `sylvester_matrix`, which takes more work for high degrees.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::{coeff, PolyError};
+    ///
+    /// assert_eq!(poly!(x^2 - 1).resultant(&poly!(x - 1), "x").unwrap(), coeff(0));
+    /// assert_eq!(poly!(x^2 + 1).resultant(&poly!(x - 2), "x").unwrap(), coeff(5));
+    /// let multivariate = poly!(x^2 - y).resultant(&poly!(x - 1), "x");
+    /// assert!(matches!(multivariate, Err(PolyError::NotUnivariate { .. })));
+    /// ```
+    pub fn resultant(&self, other: &Polynomial, var: &str) -> Result<Coeff, PolyError> {
+        let mut a = self.to_coeff_vec(var)?;
+        let mut b = other.to_coeff_vec(var)?;
+        if a.is_empty() || b.is_empty() {
+            return Ok(Coeff::zero());
+        }
+        let mut result = Coeff::one();
+        loop {
+            let (m, n) = (a.len() - 1, b.len() - 1);
+            if n == 0 {
+                return Ok(result * Pow::pow(&b[0], m as i32));
+            }
+            if m * n % 2 == 1 {
+                result = -result;
+            }
+            if m < n {
+                std::mem::swap(&mut a, &mut b);
+                continue;
+            }
+            let mut r = a;
+            while r.len() >= b.len() {
+                let factor = &r[r.len() - 1] / &b[n];
+                let shift = r.len() - b.len();
+                for (i, c) in b.iter().enumerate() {
+                    r[shift + i] -= &factor * c;
+                }
+                r.pop();
+                while r.last().is_some_and(|c| c.is_zero()) {
+                    r.pop();
+                }
+            }
+            if r.is_empty() {
+                return Ok(Coeff::zero());
+            }
+            result *= Pow::pow(&b[n], (m - (r.len() - 1)) as i32);
+            a = b;
+            b = r;
+        }
+    }
+
+    /// Factors a polynomial in `var` over the rationals as far as its rational roots go. Returns a constant and a list of
+    /// factors with their multiplicities, whose product is the polynomial: 2x^3-2x gives 2 and [(x, 1), (x-1, 1), (x+1, 1)].
+    /// By the rational root theorem every rational root is d/e for a divisor d of the constant term and a divisor e of the
+    /// leading coefficient of the primitive part, and each one found gives a factor ex-d. Whatever is left has no rational
+    /// roots and is kept as one more factor; for a quadratic that means its roots are irrational or complex, so it does
+    /// not split over the rationals. A polynomial that is not in `var` alone with whole, non-negative powers is returned
+    /// as its content and a single factor.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::coeff;
+    ///
+    /// let (content, factors) = poly!(2 x^3 - 2 x).factor_rational("x");
+    /// assert_eq!(content, coeff(2));
+    /// assert_eq!(factors, [(poly!(x), 1), (poly!(x - 1), 1), (poly!(x + 1), 1)]);
+    ///
+    /// // Without rational roots, nothing splits off
+    /// assert_eq!(poly!(x^2 - 2).factor_rational("x"), (coeff(1), vec![(poly!(x^2 - 2), 1)]));
+    /// assert_eq!(poly!(x^3 + x + 1).factor_rational("x"), (coeff(1), vec![(poly!(x^3 + x + 1), 1)]));
+    /// ```
+    pub fn factor_rational(&self, var: &str) -> (Coeff, Vec<(Polynomial, u32)>) {
+        let mut p = self.clone();
+        p.apply_pow();
+        if p.is_constant() {
+            return (p.terms[0].coefficient.clone(), vec![]);
+        }
+        let (mut content, mut rest) = p.primitive_part();
+        if rest.leading_term_in(var).coefficient.is_negative() {
+            content = -content;
+            rest = -rest;
+        }
+        let coeffs = match rest.to_coeff_vec(var) {
+            Ok(coeffs) => coeffs,
+            Err(_) => return (content, vec![(rest, 1)]),
+        };
+        let mut factors = Vec::new();
+        // Zero as a root: the lowest power of var divides every term
+        let zeros = coeffs.iter().take_while(|c| c.is_zero()).count();
+        if zeros > 0 {
+            factors.push((Polynomial::var(var), zeros as u32));
+            rest = Polynomial::from_coeff_vec(var, &coeffs[zeros..]);
+        }
+        let coeffs = rest.to_coeff_vec(var).unwrap_or_default();
+        // A numerator too large to list its divisors gives no candidates, and the polynomial is kept whole
+        let numer = |c: &Coeff| c.numer().to_i64().unwrap_or(0);
+        let (constant, leading) = (numer(&coeffs[0]), numer(&coeffs[coeffs.len() - 1]));
+        let mut candidates: Vec<Coeff> = Vec::new();
+        for d in divisors(constant) {
+            for e in divisors(leading) {
+                for root in [coeff(d) / coeff(e), coeff(-d) / coeff(e)] {
+                    if !candidates.contains(&root) {
+                        candidates.push(root);
+                    }
+                }
+            }
+        }
+        for root in candidates {
+            if rest.degree_in(var) < 1.into() {
+                break;
+            }
+            // ex - d with the root d/e in lowest terms
+            let linear = Polynomial::from_coeff_vec(
+                var,
+                &[(-root.numer()).into(), root.denom().clone().into()],
+            );
+            let mut multiplicity = 0;
+            loop {
+                let (quotient, remainder) = rest.div_rem(&linear);
+                if !remainder.is_zero() {
+                    break;
+                }
+                rest = quotient;
+                multiplicity += 1;
+            }
+            if multiplicity > 0 {
+                factors.push((linear, multiplicity));
+            }
+        }
+        if !rest.is_constant() {
+            factors.push((rest, 1));
+        } else {
+            // Only 1 or -1 can be left once the linear factors have been taken out of the primitive part
+            content *= &rest.terms[0].coefficient;
+        }
+        (content, factors)
+    }
+
+    /// Factors a polynomial in `var` into factors that are irreducible over the rationals, using `factor_rational` for the
+    /// linear factors and Kronecker's method for the rest, like x^4+4 = (x^2-2x+2)(x^2+2x+2). Repeated factors appear
+    /// once for each time they divide the polynomial, and a content other than 1 comes first as a constant, so the product
+    /// of the factors is the polynomial. Factors that would need more than `KRONECKER_CANDIDATES` trial divisions to split
+    /// are left as they are; use `factor_kronecker_bounded` to change the limit.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// assert_eq!(poly!(x^4 + 4).factor_kronecker("x"), [poly!(x^2 + 2 x + 2), poly!(x^2 - 2 x + 2)]);
+    /// assert_eq!(poly!(x^4 + x^2 + 1).factor_kronecker("x"), [poly!(x^2 + x + 1), poly!(x^2 - x + 1)]);
+    /// assert_eq!(poly!(2 x^2 - 2).factor_kronecker("x"), [poly!(2), poly!(x - 1), poly!(x + 1)]);
+    ///
+    /// // Too few candidates to find a factor leave the polynomial whole
+    /// assert_eq!(poly!(x^4 + 4).factor_kronecker_bounded("x", 1), [poly!(x^4 + 4)]);
+    /// ```
+    pub fn factor_kronecker(&self, var: &str) -> Vec<Polynomial> {
+        self.factor_kronecker_bounded(var, KRONECKER_CANDIDATES)
+    }
+
+    /// Like `factor_kronecker`, trying at most `max_candidates` candidate factors for each degree before giving up and
+    /// leaving the factor as it is.
+    pub fn factor_kronecker_bounded(&self, var: &str, max_candidates: usize) -> Vec<Polynomial> {
+        let (content, factors) = self.factor_rational(var);
+        let mut result = Vec::new();
+        if !content.is_one() {
+            result.push(Polynomial::constant(content));
+        }
+        for (factor, multiplicity) in factors {
+            let split = kronecker_split(&factor, var, max_candidates);
+            for _ in 0..multiplicity {
+                result.extend(split.iter().cloned());
+            }
+        }
+        result
+    }
+
+    /// Splits a polynomial in `var` into square-free factors with Yun's algorithm, returning each factor with its
+    /// multiplicity, lowest multiplicity first: (x-1)^2(x+3) gives [(x+3, 1), (x-1, 2)]. Every factor has integer
+    /// coefficients without a common factor and a positive leading coefficient, so the product of the factors raised to
+    /// their multiplicities is the polynomial up to a constant. Constants have no factors, and a polynomial that is not
+    /// in `var` alone with whole, non-negative powers is returned as a single factor of multiplicity 1.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// let p = poly!(x^3 + x^2 - 5 x + 3); // (x-1)^2 (x+3)
+    /// assert_eq!(p.squarefree_factorization("x"), [(poly!(x + 3), 1), (poly!(x - 1), 2)]);
+    ///
+    /// // (x-1)^3 (2x+1)^2 x, up to the constant -5
+    /// let p = poly!(-20 x^6 + 40 x^5 - 5 x^4 - 35 x^3 + 15 x^2 + 5 x);
+    /// let product = p
+    ///     .squarefree_factorization("x")
+    ///     .into_iter()
+    ///     .fold(poly!(1), |product, (factor, k)| product * factor.powi(k));
+    /// assert_eq!(product * -5, p);
+    /// ```
+    pub fn squarefree_factorization(&self, var: &str) -> Vec<(Polynomial, u32)> {
+        let mut p = self.clone();
+        p.apply_pow();
+        if p.is_constant() {
+            return vec![];
+        }
+        if p.to_coeff_vec(var).is_err() {
+            return vec![(p, 1)];
+        }
+        let normalized = |f: &Polynomial| {
+            let (_, f) = f.primitive_part();
+            if f.leading_term_in(var).coefficient.is_negative() {
+                -f
+            } else {
+                f
+            }
+        };
+        let derivative = p.derivative(var);
+        let a = p.gcd(&derivative);
+        let mut b = p.div_rem(&a).0;
+        let mut d = derivative.div_rem(&a).0 - b.derivative(var);
+        let mut factors = Vec::new();
+        let mut multiplicity = 1;
+        while !b.is_constant() {
+            let a = b.gcd(&d);
+            b = b.div_rem(&a).0;
+            d = d.div_rem(&a).0 - b.derivative(var);
+            if !a.is_constant() {
+                factors.push((normalized(&a), multiplicity));
+            }
+            multiplicity += 1;
+        }
+        factors
+    }
+
+    /// Returns the Sylvester matrix of two polynomials in `var` of degrees m and n: n rows with the coefficients of this
+    /// polynomial and m rows with those of `other`, from the highest power down, each row shifted one column to the right.
+    /// Its determinant is the resultant. The errors are the same as in `to_coeff_vec`.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::{coeff, determinant};
+    ///
+    /// let (a, b) = (poly!(2 x^3 - x + 5), poly!(1/2 x^2 - 3));
+    /// let matrix = a.sylvester_matrix(&b, "x").unwrap();
+    /// assert_eq!(matrix.len(), 5);
+    /// // The missing x^2 term of a is a zero
+    /// assert_eq!(matrix[0], [coeff(2), coeff(0), coeff(-1), coeff(5), coeff(0)]);
+    /// assert_eq!(determinant(&matrix), a.resultant(&b, "x").unwrap());
+    /// ```
+    pub fn sylvester_matrix(
+        &self,
+        other: &Polynomial,
+        var: &str,
+    ) -> Result<Vec<Vec<Coeff>>, PolyError> {
+        // to_coeff_vec checks that both are polynomials in var alone
+        let m = self.to_coeff_vec(var)?.len().saturating_sub(1);
+        let n = other.to_coeff_vec(var)?.len().saturating_sub(1);
+        let size = m + n;
+        let rows = |p: &Polynomial, degree: usize, count: usize| -> Vec<Vec<Coeff>> {
+            (0..count)
+                .map(|shift| {
+                    let mut row = vec![Coeff::zero(); size];
+                    for k in 0..=degree {
+                        row[shift + degree - k] = p.coefficient(var, (k as i64).into()).terms[0]
+                            .coefficient
+                            .clone();
+                    }
+                    row
+                })
+                .collect()
+        };
+        let mut matrix = rows(self, m, n);
+        matrix.extend(rows(other, n, m));
+        Ok(matrix)
+    }
+
+    /// Returns the discriminant of the polynomial as a polynomial in `var`, which is 0 exactly when it has a repeated root:
+    /// (-1)^(n(n-1)/2) res(p, p') / a for a polynomial of degree n with leading coefficient a, like b^2-4ac for ax^2+bx+c.
+    /// The coefficients may contain other variables, and the resultant is then the determinant of the Sylvester matrix.
+    /// Constants have no discriminant, and a polynomial of degree 1 has a discriminant of 1.
+    pub fn discriminant(&self, var: &str) -> Result<Polynomial, PolyError> {
+        let coeffs = self.coefficients_in(var)?;
+        let n = coeffs.len() - 1;
+        if n == 0 {
+            return Err(PolyError::UnsupportedDegree {
+                var: var.to_string(),
+                degree: 0.into(),
+            });
+        }
+        let derivative: Vec<Polynomial> = (1..=n)
+            .map(|k| coeffs[k].clone() * coeff(k as i64))
+            .collect();
+        // The Sylvester matrix has n-1 shifted rows of p's coefficients and n shifted rows of p''s, highest power first
+        let size = 2 * n - 1;
+        let row = |c: &[Polynomial], shift: usize| -> Vec<Polynomial> {
+            let mut row = vec![Polynomial::zero(); size];
+            for (k, coeff) in c.iter().rev().enumerate() {
+                row[shift + k] = coeff.clone();
+            }
+            row
+        };
+        let mut matrix: Vec<Vec<Polynomial>> = (0..n - 1).map(|i| row(&coeffs, i)).collect();
+        matrix.extend((0..n).map(|i| row(&derivative, i)));
+        let resultant = polynomial_determinant(matrix);
+        let (mut quotients, _) = resultant.reduce(&[coeffs[n].clone()], MonomialOrder::default());
+        let discriminant = quotients.remove(0);
+        Ok(if n * (n - 1) / 2 % 2 == 1 {
+            -discriminant
+        } else {
+            discriminant
+        })
+    }
+
+    /// Returns the Chebyshev polynomial of the first kind T_n in `var`, from T_0 = 1, T_1 = x and T_(k+1) = 2x T_k - T_(k-1).
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::Polynomial;
+    ///
+    /// let known = [
+    ///     poly!(1),
+    ///     poly!(x),
+    ///     poly!(2 x^2 - 1),
+    ///     poly!(4 x^3 - 3 x),
+    ///     poly!(8 x^4 - 8 x^2 + 1),
+    ///     poly!(16 x^5 - 20 x^3 + 5 x),
+    ///     poly!(32 x^6 - 48 x^4 + 18 x^2 - 1),
+    /// ];
+    /// for (n, t) in known.into_iter().enumerate() {
+    ///     assert_eq!(Polynomial::chebyshev("x", n as u32), t);
+    /// }
+    /// ```
+    pub fn chebyshev(var: &str, n: u32) -> Polynomial {
+        let x = Polynomial::var(var);
+        let (mut previous, mut current) = (Polynomial::one(), x.clone());
+        if n == 0 {
+            return previous;
+        }
+        for _ in 1..n {
+            let next = 2 * x.clone() * current.clone() - previous;
+            previous = std::mem::replace(&mut current, next);
+        }
+        current
+    }
+
+    /// Returns the Legendre polynomial P_n in `var`, from P_0 = 1, P_1 = x and (k+1) P_(k+1) = (2k+1) x P_k - k P_(k-1).
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::Polynomial;
+    ///
+    /// let known = [
+    ///     poly!(1),
+    ///     poly!(x),
+    ///     poly!(3/2 x^2 - 1/2),
+    ///     poly!(5/2 x^3 - 3/2 x),
+    ///     poly!(35/8 x^4 - 15/4 x^2 + 3/8),
+    ///     poly!(63/8 x^5 - 35/4 x^3 + 15/8 x),
+    ///     poly!(231/16 x^6 - 315/16 x^4 + 105/16 x^2 - 5/16),
+    /// ];
+    /// for (n, p) in known.into_iter().enumerate() {
+    ///     assert_eq!(Polynomial::legendre("x", n as u32), p);
+    /// }
+    /// ```
+    pub fn legendre(var: &str, n: u32) -> Polynomial {
+        let x = Polynomial::var(var);
+        let (mut previous, mut current) = (Polynomial::one(), x.clone());
+        if n == 0 {
+            return previous;
+        }
+        for k in 1..n as i64 {
+            let next = (coeff(2 * k + 1) / coeff(k + 1) * x.clone() * current.clone())
+                - coeff(k) / coeff(k + 1) * previous;
+            previous = std::mem::replace(&mut current, next);
+        }
+        current
+    }
+
+    /// Returns (1+x)^n in `var`, multiplying by 1+x one step at a time, so its coefficients are the binomial coefficients.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    /// use sym_tfg::polynomial::Polynomial;
+    ///
+    /// assert_eq!(Polynomial::binomial_expansion("x", 0), poly!(1));
+    /// assert_eq!(
+    ///     Polynomial::binomial_expansion("x", 6),
+    ///     poly!(x^6 + 6 x^5 + 15 x^4 + 20 x^3 + 15 x^2 + 6 x + 1)
+    /// );
+    /// ```
+    pub fn binomial_expansion(var: &str, n: u32) -> Polynomial {
+        let one_plus_x = 1 + Polynomial::var(var);
+        let mut result = Polynomial::one();
+        for _ in 0..n {
+            result *= &one_plus_x;
+        }
+        result
+    }
+
+    /// Returns the polynomial in `var` of lowest degree that goes through every point, with Newton's divided differences.
+    /// A point given twice is only used once, but two points with the same x and different y values give an error.
+    /// No points at all give the zero polynomial.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::{coeff, PolyError, Polynomial};
+    ///
+    /// let half = coeff(1) / coeff(2);
+    /// let points = [(coeff(0), coeff(1)), (coeff(1), coeff(2)), (coeff(2), coeff(5)), (half, coeff(-3))];
+    /// let p = Polynomial::interpolate("x", &points).unwrap();
+    /// for (x, y) in &points {
+    ///     assert_eq!(p.eval_at(&[("x".to_string(), x.clone())]).unwrap(), *y);
+    /// }
+    ///
+    /// // A repeated point is used once, but two values at the same x conflict
+    /// let repeated = [(coeff(0), coeff(1)), (coeff(1), coeff(2)), (coeff(0), coeff(1))];
+    /// assert_eq!(Polynomial::interpolate("x", &repeated).unwrap().as_string(), "x+1");
+    /// let conflicting = [(coeff(0), coeff(1)), (coeff(0), coeff(2))];
+    /// assert!(matches!(Polynomial::interpolate("x", &conflicting), Err(PolyError::ConflictingPoints { .. })));
+    /// ```
+    pub fn interpolate(var: &str, points: &[(Coeff, Coeff)]) -> Result<Polynomial, PolyError> {
+        let mut unique: Vec<(Coeff, Coeff)> = Vec::new();
+        for (x, y) in points {
+            match unique.iter().find(|(ux, _)| ux == x) {
+                Some((_, uy)) if uy != y => {
+                    return Err(PolyError::ConflictingPoints {
+                        x: x.clone(),
+                        y1: uy.clone(),
+                        y2: y.clone(),
+                    })
+                }
+                Some(_) => {}
+                None => unique.push((x.clone(), y.clone())),
+            }
+        }
+        Ok(Polynomial::from_coeff_vec(
+            var,
+            &interpolation_coeffs(&unique)?,
+        ))
+    }
+
+    /// Builds the monic polynomial in `var` whose roots are exactly `roots`, as the product of every `var - r`.
+    /// A repeated root gets a higher multiplicity, and no roots at all give the constant 1.
+    pub fn from_roots(var: &str, roots: &[Coeff]) -> Polynomial {
+        let mut result = Polynomial::one();
+        for root in roots {
+            result *= Polynomial::var(var) - Polynomial::constant(root.clone());
+        }
+        result
+    }
+
+    /// Finds the roots (numerical or symbolic) of the polynomial. A cubic is solved through its rational roots, so one
+    /// without any gives `UnsupportedDegree`.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::{coeff, PolyError, Polynomial};
+    /// use sym_tfg::{poly, ratio};
+    ///
+    /// let roots = poly!(4 x^2 - 1).roots("x").unwrap();
+    /// assert_eq!(roots[1], vec![ratio!(2; 4)]);
+    /// assert_eq!(roots[2], vec![ratio!(-1; 2)]);
+    ///
+    /// let cubic = Polynomial::from_roots("x", &[coeff(1), coeff(-2), coeff(1) / coeff(3)]);
+    /// assert_eq!(cubic.roots("x").unwrap().iter().filter(|r| !r.is_empty()).count(), 3);
+    /// assert!(matches!(poly!(x^3 - 2).roots("x"), Err(PolyError::UnsupportedDegree { .. })));
+    /// ```
+    pub fn roots(&self, var: &str) -> Result<Vec<Vec<PolyRatio>>, PolyError> {
+        let mut result = vec![Vec::new()];
+        let mut self_copy = self.clone();
+        self_copy.simplify();
+        if self_copy.degree().is_none() {
+            return Err(PolyError::ZeroPolynomial {
+                var: var.to_string(),
+            });
+        }
+        if self_copy.is_constant() {
+            return Err(PolyError::UnsupportedDegree {
+                var: var.to_string(),
+                degree: 0.into(),
+            });
+        }
+
+        // Find out the degree of the polynomial, but only taking into account the variable var
+        let degree = self_copy
             .terms
             .iter()
             .map(|t| {
@@ -531,147 +3447,39 @@ impl Polynomial {
             d if d == 1.into() => {
                 // If the degree is 1, the polynomial is linear: ax + b = 0
                 // That means x = -b/a
-                let (a_term, a) = self_copy.find_sym_coeff(var, 1.into());
-                /*
-                println!("a: {:?}", a);
-                println!(
-                    "a_term: {:?}",
-                    Polynomial {
-                        terms: vec![a_term.clone()],
-                        degree: 1.into(),
-                    }
-                    .as_string()
-                );
-                */
-                let b = self.clone()
-                    - Polynomial {
-                        terms: vec![a_term],
-                        degree: 1.into(),
-                    };
-
-                // println!("b: {:?}", b);
-                let minus_b = PolyRatio::from(b)
-                    * PolyRatio::from(Polynomial {
-                        terms: vec![Term {
-                            coefficient: Rational64::new(-1, 1),
-                            variables: vec![],
-                        }],
-                        degree: 1.into(),
-                    });
-                let root = minus_b
-                    / PolyRatio::from(Polynomial {
-                        terms: vec![a],
-                        degree: 1.into(),
-                    });
+                let a = self_copy.coefficient(var, 1.into());
+                let b = self_copy.coefficient(var, 0.into());
+                let mut root = -PolyRatio::from(b);
+                root /= PolyRatio::from(a);
                 result.push(vec![root]);
             }
             d if d == 2.into() => {
                 // If the degree is 2, the polynomial is quadratic: ax² + bx + c = 0
                 // That means x = (-b ± sqrt(b² - 4ac)) / 2a
                 // TODO: Implement symbolic roots
+                let a = self_copy.coefficient(var, 2.into());
+                let b = self_copy.coefficient(var, 1.into());
 
-                // Find the term with x² by filtering the terms with the variable x and degree 2
-                let (a_term, a) = self.find_sym_coeff(var, 2.into());
-                let (b_term, b) = self.find_sym_coeff(var, 1.into());
-                let c: Polynomial = self.clone()
-                    - Polynomial {
-                        terms: vec![a_term.clone(), b_term.clone()],
-                        degree: 1.into(),
-                    };
-                /*
-                println!("a: {:?}", a);
-                println!(
-                    "a_term: {:?}",
-                    Polynomial {
-                        terms: vec![a_term],
-                        degree: 1.into(),
-                    }
-                    .as_string()
-                );
-                println!("b: {:?}", b);
-                println!(
-                    "b_term: {:?}",
-                    Polynomial {
-                        terms: vec![b_term],
-                        degree: 1.into(),
-                    }
-                    .as_string()
-                );
-                println!("c: {:?}", c.as_string());
-                println!("Polynomial: {}", self.as_string());
-                */
-
-                let minus_b = PolyRatio::from(Polynomial {
-                    terms: vec![Term {
-                        coefficient: Rational64::new(-1, 1),
-                        variables: vec![],
-                    }],
-                    degree: 1.into(),
-                }) * PolyRatio::from(Polynomial {
-                    terms: vec![b.clone()],
-                    degree: 1.into(),
-                });
-                let mut b_squared = PolyRatio::from(Polynomial {
-                    terms: vec![b.clone()],
-                    degree: 2.into(),
-                });
-                b_squared.simplify();
-                let four_ac = PolyRatio::from(Polynomial {
-                    terms: vec![Term {
-                        coefficient: Rational64::new(4, 1),
-                        variables: vec![],
-                    }],
-                    degree: 1.into(),
-                }) * PolyRatio::from(Polynomial {
-                    terms: vec![a.clone()],
-                    degree: 1.into(),
-                }) * c.clone();
-                // println!("b_squared: {}", b_squared.as_string());
-                // println!("four_ac: {}", four_ac.as_string());
-                let discriminant = b_squared.clone() - four_ac.clone();
+                let minus_b = -PolyRatio::from(b.clone());
+                let discriminant = PolyRatio::from(self_copy.discriminant(var)?);
                 let mut sqrt_discriminant = discriminant.clone();
-                if (discriminant.numerator.terms[0].coefficient < 0.into())
-                    ^ (discriminant.denominator.terms[0].coefficient < 0.into())
-                {
-                    sqrt_discriminant = discriminant.clone()
-                        * PolyRatio::from(Polynomial {
-                            terms: vec![Term {
-                                coefficient: Rational64::new(-1, 1),
-                                variables: vec![],
-                            }],
-                            degree: 1.into(),
-                        })
-                        * PolyRatio::from(Polynomial {
-                            terms: vec![Term {
-                                coefficient: Rational64::new(1, 1),
-                                variables: vec![Variable {
-                                    name: "\u{2148}".to_string(),
-                                    degree: Rational64::new(2, 1),
-                                }],
-                            }],
-                            degree: 1.into(),
-                        });
+                // Only a discriminant with every coefficient negative is taken as negative, whatever the order of its terms
+                let negative = |p: &Polynomial| p.terms.iter().all(|t| t.coefficient.is_negative());
+                if negative(&discriminant.numerator) ^ negative(&discriminant.denominator) {
+                    let i_squared = Polynomial::builder().term(1, &[("\u{2148}", 2)]).build()?;
+                    sqrt_discriminant = -discriminant.clone() * PolyRatio::from(i_squared);
                     println!("(\u{2148} is the imaginary unit)");
                     // println!("{}", discriminant.as_string());
                     // panic!("Imaginary roots not supported yet!");
                 }
-                sqrt_discriminant.numerator.degree = Rational64::new(1, 2);
-                sqrt_discriminant.denominator.degree = Rational64::new(1, 2);
+                sqrt_discriminant.numerator.pending_pow = Rational64::new(1, 2);
+                sqrt_discriminant.denominator.pending_pow = Rational64::new(1, 2);
                 // println!("Discriminant: {}", discriminant.as_string());
                 sqrt_discriminant.simplify();
                 // println!("Discriminant: {}", discriminant.as_string());
-                let two_a = PolyRatio::from(Polynomial {
-                    terms: vec![Term {
-                        coefficient: Rational64::new(2, 1),
-                        variables: vec![],
-                    }],
-                    degree: 1.into(),
-                }) * PolyRatio::from(Polynomial {
-                    terms: vec![a.clone()],
-                    degree: 1.into(),
-                });
+                let two_a = PolyRatio::from(2 * a.clone());
                 // println!("Two a: {}", two_a.as_string());
-                if sqrt_discriminant.numerator.degree != Rational64::new(1, 1) {
+                if sqrt_discriminant.numerator.pending_pow != Rational64::new(1, 1) {
                     // println!("here");
                     let root1 = vec![
                         minus_b.clone() / two_a.clone(),
@@ -679,14 +3487,7 @@ impl Polynomial {
                     ];
                     let root2 = vec![
                         minus_b.clone() / two_a.clone(),
-                        sqrt_discriminant.clone()
-                            / (PolyRatio::from(Polynomial {
-                                terms: vec![Term {
-                                    coefficient: Rational64::new(-1, 1),
-                                    variables: vec![],
-                                }],
-                                degree: 1.into(),
-                            }) * two_a.clone()),
+                        sqrt_discriminant.clone() / -two_a.clone(),
                     ];
                     result.push(root1);
                     result.push(root2);
@@ -699,43 +3500,225 @@ impl Polynomial {
             }
             d if d == 3.into() => {
                 // If the degree is 3, the polynomial is cubic: ax³ + bx² + cx + d = 0
+                // Only its rational roots are found, each one from a linear factor, and the quadratic factor that may be
+                // left is solved like above. A cubic without rational roots is not solved
+                let (_, factors) = self_copy.factor_rational(var);
+                if factors.iter().all(|(f, _)| f.degree_in(var) == degree) {
+                    return Err(PolyError::UnsupportedDegree {
+                        var: var.to_string(),
+                        degree,
+                    });
+                }
+                for (factor, multiplicity) in factors {
+                    let roots = factor.roots(var)?;
+                    for _ in 0..multiplicity {
+                        result.extend(roots.iter().filter(|r| !r.is_empty()).cloned());
+                    }
+                }
             }
             _ => {
-                panic!("Higher degree polynomials not supported yet!");
+                return Err(PolyError::UnsupportedDegree {
+                    var: var.to_string(),
+                    degree,
+                });
             }
         }
-        return result;
+        return Ok(result);
     }
 }
 
-impl Add for Polynomial {
-    type Output = Self;
+/// A number `PolynomialBuilder` takes as a coefficient or a power: an integer, a `Rational64`, or a fraction given
+/// as a `(numerator, denominator)` pair.
+pub trait BuilderNumber: Copy {
+    /// Returns the number as a fraction, or `None` if its denominator is zero.
+    fn to_rational(self) -> Option<Rational64>;
+}
 
-    fn add(self, other: Self) -> Self {
-        let mut result = self.terms.clone();
-        result.extend(other.terms);
-        let mut sum = Polynomial {
-            terms: result,
-            degree: 1.into(),
+impl BuilderNumber for i32 {
+    fn to_rational(self) -> Option<Rational64> {
+        Some((self as i64).into())
+    }
+}
+
+impl BuilderNumber for i64 {
+    fn to_rational(self) -> Option<Rational64> {
+        Some(self.into())
+    }
+}
+
+impl BuilderNumber for Rational64 {
+    fn to_rational(self) -> Option<Rational64> {
+        // `Ratio::new_raw` can build a fraction over zero without panicking
+        (!self.denom().is_zero()).then_some(self)
+    }
+}
+
+impl BuilderNumber for (i64, i64) {
+    fn to_rational(self) -> Option<Rational64> {
+        (self.1 != 0).then(|| Rational64::new(self.0, self.1))
+    }
+}
+
+/// Builds a polynomial one term at a time: `Polynomial::builder().term(3, &[("x", 2)]).term(-1, &[]).build()`.
+/// A fraction with a zero denominator does not panic, it makes `build` return an error instead.
+///
+/// ```
+/// use num::rational::Rational64;
+/// use sym_tfg::poly;
+/// use sym_tfg::polynomial::{PolyError, Polynomial};
+///
+/// let p = Polynomial::builder()
+///     .term(3, &[("x", 2)])
+///     .term(5_i64, &[("x", 2)])
+///     .term(Rational64::new(1, 2), &[("x", 1), ("y", 1)])
+///     .term((-3, 4), &[])
+///     .fractional_term(2, &[("y", (1, 2))])
+///     .build()
+///     .unwrap();
+/// assert_eq!(p, poly!(8 x^2 + 1/2 x y - 3/4 + 2 y^(1/2)));
+///
+/// let zero = Polynomial::builder().term((1, 0), &[("x", 1)]).build();
+/// assert!(matches!(zero, Err(PolyError::ZeroDenominator)));
+/// let zero = Polynomial::builder().term(Rational64::new_raw(1, 0), &[]).build();
+/// assert!(matches!(zero, Err(PolyError::ZeroDenominator)));
+/// let zero = Polynomial::builder().fractional_term(1, &[("x", (1, 0))]).build();
+/// assert!(matches!(zero, Err(PolyError::ZeroDenominator)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PolynomialBuilder {
+    terms: Vec<Term>,
+    error: Option<PolyError>,
+}
+
+impl PolynomialBuilder {
+    /// Adds a term with each variable raised to a whole power.
+    pub fn term(self, coefficient: impl BuilderNumber, variables: &[(&str, i64)]) -> Self {
+        self.fractional_term(coefficient, variables)
+    }
+
+    /// Adds a term with each variable raised to a power that can be a fraction, like `&[("x", (1, 2))]`.
+    pub fn fractional_term(
+        mut self,
+        coefficient: impl BuilderNumber,
+        variables: &[(&str, impl BuilderNumber)],
+    ) -> Self {
+        let coefficient = coefficient.to_rational();
+        let degrees: Option<Vec<Rational64>> =
+            variables.iter().map(|(_, d)| d.to_rational()).collect();
+        match (coefficient, degrees) {
+            (Some(coefficient), Some(degrees)) => self.terms.push(Term {
+                coefficient: degree_coeff(coefficient),
+                variables: variables
+                    .iter()
+                    .zip(degrees)
+                    .map(|((name, _), degree)| Variable {
+                        name: name.to_string(),
+                        degree,
+                    })
+                    .collect(),
+            }),
+            _ => {
+                self.error.get_or_insert(PolyError::ZeroDenominator);
+            }
+        }
+        self
+    }
+
+    /// Adds a term that has already been built.
+    pub fn push(mut self, term: Term) -> Self {
+        self.terms.push(term);
+        self
+    }
+
+    /// Returns the sum of the terms, simplified, or the error of the first term that could not be added.
+    pub fn build(self) -> Result<Polynomial, PolyError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let mut p = Polynomial {
+            terms: self.terms,
+            pending_pow: 1.into(),
         };
-        sum.simplify();
-        sum
+        p.simplify();
+        Ok(p)
     }
 }
 
-impl Sub for Polynomial {
+impl<T: Coefficient> Add for Polynomial<T> {
     type Output = Self;
 
-    fn sub(self, mut other: Self) -> Self {
-        for term in &mut other.terms {
-            term.coefficient *= -1;
-        }
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+/// Adds in place, moving the terms of `other` into the accumulator instead of cloning it.
+impl<T: Coefficient> AddAssign for Polynomial<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.terms.extend(other.terms);
+        self.pending_pow = 1.into();
+        self.simplify();
+    }
+}
+
+impl<T: Coefficient> AddAssign<&Polynomial<T>> for Polynomial<T> {
+    fn add_assign(&mut self, other: &Polynomial<T>) {
+        self.terms.extend(other.terms.iter().cloned());
+        self.pending_pow = 1.into();
+        self.simplify();
+    }
+}
+
+impl<T: Coefficient> Sub for Polynomial<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + (-other)
+    }
+}
+
+/// Negates every coefficient. Nothing is simplified, so negating twice gives back exactly the same polynomial.
+impl<T: Coefficient> Neg for Polynomial<T> {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        self.terms = self.terms.into_iter().map(|t| -t).collect();
+        self
+    }
+}
+
+impl<T: Coefficient> SubAssign for Polynomial<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self += -other;
+    }
+}
+
+impl<T: Coefficient> SubAssign<&Polynomial<T>> for Polynomial<T> {
+    fn sub_assign(&mut self, other: &Polynomial<T>) {
+        self.terms.extend(other.terms.iter().map(|t| -t.clone()));
+        self.pending_pow = 1.into();
+        self.simplify();
+    }
+}
+
+impl<T: Coefficient> MulAssign for Polynomial<T> {
+    fn mul_assign(&mut self, other: Self) {
+        let lhs = std::mem::take(&mut self.terms);
+        *self = Polynomial {
+            terms: lhs,
+            pending_pow: self.pending_pow,
+        } * other;
+    }
+}
 
-        self.add(other)
+impl<T: Coefficient> MulAssign<&Polynomial<T>> for Polynomial<T> {
+    fn mul_assign(&mut self, other: &Polynomial<T>) {
+        *self *= other.clone();
     }
 }
 
-impl Mul for Polynomial {
+impl<T: Coefficient> Mul for Polynomial<T> {
     type Output = Self;
 
     fn mul(self, other: Self) -> Self {
@@ -743,10 +3726,10 @@ impl Mul for Polynomial {
         if other
             == (Polynomial {
                 terms: vec![Term {
-                    coefficient: Rational64::new(1, 1),
+                    coefficient: T::one(),
                     variables: vec![],
                 }],
-                degree: 1.into(),
+                pending_pow: 1.into(),
             })
         {
             return self;
@@ -756,7 +3739,10 @@ impl Mul for Polynomial {
                 let mut new_vars = term1.variables.clone();
                 new_vars.extend(term2.variables.clone());
                 let mut new_term = Term {
-                    coefficient: term1.coefficient * term2.coefficient,
+                    coefficient: term1
+                        .coefficient
+                        .try_mul(&term2.coefficient)
+                        .unwrap_or_else(|| overflow()),
                     variables: new_vars,
                 };
                 new_term.sort_vars();
@@ -766,35 +3752,38 @@ impl Mul for Polynomial {
         }
         let mut product = Polynomial {
             terms: result,
-            degree: 1.into(), // FIXME !!
+            pending_pow: 1.into(), // FIXME !!
         };
         product.simplify();
         product
     }
 }
 
+/// Dividing by the zero polynomial does not panic: it gives a ratio with a zero denominator, see `PolyRatio::new`.
 impl Div for Polynomial {
     type Output = PolyRatio;
     fn div(self, other: Self) -> PolyRatio {
         let mut dividend = self.clone();
         dividend.simplify();
 
-        if dividend.terms.len() == 0 {
-            return PolyRatio::from(Polynomial {
-                terms: vec![Term {
-                    coefficient: Rational64::new(0, 1),
-                    variables: vec![],
-                }],
-                degree: 1.into(),
-            });
+        if dividend.is_zero() {
+            return PolyRatio::zero();
         }
 
         let mut divisor = other.clone();
         divisor.simplify();
 
+        if divisor.is_zero() {
+            // Keep the division by zero in the ratio, it has no quotient
+            return PolyRatio {
+                numerator: dividend,
+                denominator: divisor,
+            };
+        }
+
         let mut quotient = Polynomial {
             terms: vec![],
-            degree: 1.into(),
+            pending_pow: 1.into(),
         };
 
         let mut remainder = dividend.clone();
@@ -805,32 +3794,44 @@ impl Div for Polynomial {
         //     divisor.as_string()
         // );
 
-        let zero_poly = Polynomial {
-            terms: vec![Term {
-                coefficient: Rational64::new(0, 1),
-                variables: vec![],
-            }],
-            degree: 1.into(),
+        // A divisor in a single variable only needs the powers of that variable, and any other variables
+        // in the dividend go along in the quotient
+        let var = divisor.is_univariate();
+        if var.is_none() && !divisor.is_constant() {
+            // With several variables, divide with `reduce` and keep the fraction unless it leaves no remainder
+            let (mut quotients, remainder) =
+                dividend.reduce(&[divisor.clone()], MonomialOrder::default());
+            if remainder.is_zero() {
+                return PolyRatio::from(quotients.remove(0));
+            }
+            return PolyRatio {
+                numerator: dividend,
+                denominator: divisor,
+            };
+        }
+        let degree = |p: &Polynomial| match &var {
+            Some(var) => (!p.is_zero()).then(|| p.degree_in(var)),
+            None => p.degree(),
+        };
+        let leading_term = |p: &Polynomial| match &var {
+            Some(var) => p.leading_term_in(var),
+            None => p.leading_term(),
         };
 
-        if remainder.degree() < divisor.degree() {
+        if degree(&remainder) < degree(&divisor) {
             return PolyRatio {
                 numerator: remainder,
                 denominator: divisor,
             };
         }
 
-        while remainder != zero_poly
-            && remainder.terms.len() != 0
-            && remainder.degree() >= divisor.degree()
-        // THIS LAST CONDITION was THE PROBLEM (check what happens with 8/x)
-        {
-            let t = remainder.leading_term() / divisor.leading_term();
-            remainder._print();
+        // The zero remainder has no degree, so this also stops once the division is exact
+        while degree(&remainder) >= degree(&divisor) {
+            let t = leading_term(&remainder) / leading_term(&divisor);
             //println!("t: {:?}", t);
-            quotient = quotient + t.clone();
+            quotient += t.clone();
             //println!("Quotient: {}", quotient.as_string());
-            remainder = remainder - (divisor.clone() * t.clone());
+            remainder -= divisor.clone() * t.clone();
             remainder.simplify();
             //remainder._print();
         }
@@ -839,26 +3840,714 @@ impl Div for Polynomial {
         let ratio = PolyRatio::from(quotient);
         ratio
     }
-}
+}
+
+impl Rem for Polynomial {
+    type Output = Self;
+
+    /// The remainder of dividing by `other` with `div_rem`. The remainder of dividing by zero is the dividend.
+    ///
+    /// ```
+    /// use sym_tfg::poly;
+    ///
+    /// let pairs = [
+    ///     (poly!(x^3 - 2 x + 5), poly!(x - 2)),
+    ///     (poly!(2 x^4 + x), poly!(3 x^2 + 1/2)),
+    ///     (poly!(x + 1), poly!(x^2)),
+    ///     (poly!(x^2 - 1), poly!()),
+    /// ];
+    /// for (a, b) in pairs {
+    ///     let (quotient, _) = a.div_rem(&b);
+    ///     assert_eq!(quotient * b.clone() + a.clone() % b, a);
+    /// }
+    /// ```
+    fn rem(self, other: Self) -> Self {
+        self.div_rem(&other).1
+    }
+}
+
+impl<T: Coefficient> Zero for Polynomial<T> {
+    fn zero() -> Self {
+        Polynomial {
+            terms: vec![Term {
+                coefficient: T::zero(),
+                variables: vec![],
+            }],
+            pending_pow: 1.into(),
+        }
+    }
+
+    /// True for the empty polynomial and for any polynomial that simplifies to 0.
+    fn is_zero(&self) -> bool {
+        Polynomial::is_zero(self)
+    }
+}
+
+impl<T: Coefficient> One for Polynomial<T> {
+    fn one() -> Self {
+        Polynomial {
+            terms: vec![Term {
+                coefficient: T::one(),
+                variables: vec![],
+            }],
+            pending_pow: 1.into(),
+        }
+    }
+
+    fn is_one(&self) -> bool {
+        let mut p = self.clone();
+        p.simplify();
+        p.terms.len() == 1 && p.terms[0].variables.is_empty() && p.terms[0].coefficient.is_one()
+    }
+}
+
+impl<T: Coefficient> IntoIterator for Polynomial<T> {
+    type Item = Term<T>;
+    type IntoIter = std::vec::IntoIter<Term<T>>;
+
+    /// Yields the terms in the order `sort_terms` would put them in.
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.sort_terms();
+        self.terms.into_iter()
+    }
+}
+
+impl<'a, T: Coefficient> IntoIterator for &'a Polynomial<T> {
+    type Item = &'a Term<T>;
+    type IntoIter = std::vec::IntoIter<&'a Term<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Pow<u32> for Polynomial {
+    type Output = Polynomial;
+
+    fn pow(self, exp: u32) -> Polynomial {
+        self.powi(exp)
+    }
+}
+
+impl Pow<u32> for &Polynomial {
+    type Output = Polynomial;
+
+    fn pow(self, exp: u32) -> Polynomial {
+        self.powi(exp)
+    }
+}
+
+/// Compares the normalized forms, so the order the terms were entered in does not matter.
+impl<T: Coefficient> PartialEq for Polynomial<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let self_copy = self.normalized();
+        let other_copy = other.normalized();
+        self_copy.terms == other_copy.terms && self_copy.pending_pow == other_copy.pending_pow
+    }
+}
+
+impl<T: Coefficient + Eq> Eq for Polynomial<T> {}
+
+/// Hashes the normalized form, so polynomials that are equal have the same hash.
+///
+/// ```
+/// use std::collections::{HashMap, HashSet};
+/// use sym_tfg::poly;
+///
+/// let set: HashSet<_> = [poly!(x + 1), poly!(1 + x), poly!(x + 0 y + 1), poly!(x - 1)].into_iter().collect();
+/// assert_eq!(set.len(), 2);
+///
+/// let mut cache = HashMap::new();
+/// cache.insert(poly!(x y + 2 x), "first");
+/// cache.insert(poly!(2 x + y x), "second");
+/// assert_eq!(cache.len(), 1);
+/// assert_eq!(cache[&poly!(x y + 2 x)], "second");
+/// ```
+impl<T: Coefficient + Hash> Hash for Polynomial<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let p = self.normalized();
+        for term in &p.terms {
+            term.coefficient.hash(state);
+            term.variables.hash(state);
+        }
+        p.pending_pow.hash(state);
+    }
+}
+
+/// Orders polynomials by their normalized forms: by degree, then by their monomials from the leading one down (a
+/// polynomial whose monomials run out first is the lesser), then by their coefficients in the same order, and last by the
+/// pending power. Only equal polynomials compare equal, so sorting gives the same result whatever the input order.
+///
+/// ```
+/// use sym_tfg::poly;
+///
+/// let polys = [
+///     poly!(x^2 + 1), poly!(x + 1), poly!(y), poly!(2 x), poly!(x y), poly!(3), poly!(x^2 - x), poly!(1 + x),
+/// ];
+/// let mut sorted = polys.to_vec();
+/// sorted.sort();
+/// let expected = [
+///     poly!(3), poly!(y), poly!(2 x), poly!(x + 1), poly!(x + 1), poly!(x y), poly!(x^2 + 1), poly!(x^2 - x),
+/// ];
+/// assert_eq!(sorted, expected);
+/// for k in 0..polys.len() {
+///     let mut shuffled = polys.to_vec();
+///     shuffled.rotate_left(k);
+///     if k % 2 == 1 {
+///         shuffled.reverse();
+///     }
+///     shuffled.sort();
+///     assert_eq!(shuffled, sorted);
+/// }
+/// ```
+impl<T: Coefficient + Ord> Ord for Polynomial<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let a = self.normalized();
+        let b = other.normalized();
+        a.degree()
+            .cmp(&b.degree())
+            .then_with(|| {
+                let monomials = a.terms.iter().zip(&b.terms);
+                monomials
+                    .map(|(s, t)| s.cmp_monomial(t))
+                    .find(|o| o.is_ne())
+                    .unwrap_or_else(|| a.terms.len().cmp(&b.terms.len()))
+            })
+            .then_with(|| {
+                let coefficients = a.terms.iter().map(|t| &t.coefficient);
+                coefficients.cmp(b.terms.iter().map(|t| &t.coefficient))
+            })
+            .then_with(|| a.pending_pow.cmp(&b.pending_pow))
+    }
+}
+
+impl<T: Coefficient + Ord> PartialOrd for Polynomial<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PolyRatio<T = Coeff> {
+    pub numerator: Polynomial<T>,
+    pub denominator: Polynomial<T>,
+}
+
+impl<T: Coefficient> PolyRatio<T> {
+    /// Converts the ratio to a string, leaving out a denominator of 1. A zero denominator is an error.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::PolyError;
+    /// use sym_tfg::ratio;
+    ///
+    /// assert_eq!(ratio!(x + 1; x - 1).as_string().unwrap(), "(x+1) / (x-1)");
+    /// assert_eq!(ratio!(x + 1; 1).as_string().unwrap(), "x+1");
+    /// assert!(matches!(ratio!(x; 0).as_string(), Err(PolyError::DivisionByZero { .. })));
+    /// ```
+    pub fn as_string(&self) -> Result<String, PolyError> {
+        if self.denominator.is_zero() {
+            Err(PolyError::DivisionByZero { at: vec![] })
+        } else if self.denominator.as_string() == "1".to_string() {
+            Ok(self.numerator.as_string())
+        } else {
+            Ok(format!(
+                "({}) / ({})",
+                self.numerator.as_string(),
+                self.denominator.as_string()
+            ))
+        }
+    }
+}
+
+/// The limit of a ratio as a variable approaches a value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitResult {
+    Finite(Coeff),
+    PlusInfinity,
+    MinusInfinity,
+    /// The limit does not exist, or cannot be found, like when the ratio depends on other variables.
+    Undefined,
+}
+
+impl fmt::Display for LimitResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitResult::Finite(value) => write!(f, "{}", value),
+            LimitResult::PlusInfinity => write!(f, "inf"),
+            LimitResult::MinusInfinity => write!(f, "-inf"),
+            LimitResult::Undefined => write!(f, "undefined"),
+        }
+    }
+}
+
+/// The asymptotes of the graph of a ratio in one variable x, as given by `PolyRatio::asymptotes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Asymptotes {
+    /// The values of x of the vertical asymptotes, each one a sum of parts like the roots from `Polynomial::roots`.
+    pub vertical: Vec<Vec<PolyRatio>>,
+    /// The value y = c the ratio approaches as x grows without bound.
+    pub horizontal: Option<Coeff>,
+    /// The line the ratio approaches as x grows without bound, when that line is not horizontal.
+    pub oblique: Option<Polynomial>,
+}
+
+/// What happens to a ratio at a root of its denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingularityKind {
+    /// The root is also a root of the numerator at least as many times, so it leaves a single missing point.
+    Hole,
+    /// The ratio grows without bound. The order is the multiplicity of the root in the denominator once the common
+    /// factors are cancelled.
+    Pole(u32),
+}
+
+impl PolyRatio {
+    /// Returns the ratio of two polynomials, or an error if the denominator is zero once simplified.
+    /// The struct literal and the `/` operators accept a zero denominator instead, which is carried through any
+    /// further arithmetic until `as_string` returns it as an error.
+    pub fn new(numerator: Polynomial, mut denominator: Polynomial) -> Result<PolyRatio, PolyError> {
+        denominator.simplify();
+        if denominator.is_zero() {
+            return Err(PolyError::DivisionByZero { at: vec![] });
+        }
+        Ok(PolyRatio {
+            numerator,
+            denominator,
+        })
+    }
+
+    /// Splits the ratio into its polynomial part and a proper fraction, whose numerator has a lower degree than its
+    /// denominator, like (x^2+x+1)/(x-1) = x+2 + (3)/(x-1). A proper ratio has a zero polynomial part, and a constant
+    /// denominator leaves everything in the polynomial part. Ratios that are not in a single variable with integer
+    /// exponents cannot be divided, and are returned whole as the fraction.
+    pub fn mixed(&self) -> (Polynomial, PolyRatio) {
+        let mut ratio = self.clone();
+        ratio.simplify();
+        if ratio.denominator.is_constant() && !ratio.denominator.is_zero() {
+            let c = ratio.denominator.eval_at(&[]).unwrap();
+            return (
+                ratio.numerator * Polynomial::constant(Coeff::one() / c),
+                PolyRatio::from(Polynomial::zero()),
+            );
+        }
+        let mut vars = ratio.numerator.vars();
+        vars.extend(ratio.denominator.vars());
+        let divisible = vars.len() == 1
+            && vars.iter().all(|var| {
+                ratio.numerator.to_coeff_vec(var).is_ok()
+                    && ratio.denominator.to_coeff_vec(var).is_ok()
+            });
+        if !divisible || ratio.denominator.is_zero() {
+            return (Polynomial::zero(), ratio);
+        }
+        let (quotient, remainder) = ratio.numerator.div_rem(&ratio.denominator);
+        (
+            quotient,
+            PolyRatio {
+                numerator: remainder,
+                denominator: ratio.denominator,
+            },
+        )
+    }
+
+    /// Converts the ratio to a string like `as_string`, with the polynomial part written apart from the proper fraction
+    /// left over, as given by `mixed`.
+    pub fn as_string_mixed(&self) -> Result<String, PolyError> {
+        let (polynomial_part, fraction) = self.mixed();
+        if polynomial_part.is_zero() {
+            fraction.as_string()
+        } else if fraction.numerator.is_zero() {
+            Ok(polynomial_part.as_string())
+        } else {
+            Ok(format!(
+                "{} + {}",
+                polynomial_part.as_string(),
+                fraction.as_string()?
+            ))
+        }
+    }
+
+    /// Splits a ratio of polynomials in `var` into partial fractions, whose sum is the ratio: the polynomial part first when
+    /// the numerator has at least the degree of the denominator, then a polynomial of lower degree than each factor of the
+    /// denominator over each of its powers, like 1/(x^2-1) = (1/2)/(x-1) + (-1/2)/(x+1) and
+    /// x/((x-1)(x^2+1)) = (1/2)/(x-1) + (-1/2x+1/2)/(x^2+1). A factor f that appears m times gives the pieces A1/f up to
+    /// Am/f^m. The factors are the ones `factor_kronecker` finds, so they are irreducible over the rationals unless it
+    /// gave up on splitting one.
+    ///
+    /// ```
+    /// use sym_tfg::ratio;
+    ///
+    /// // The second denominator is (x^2+1)^2 (x-2)
+    /// for r in [ratio!(x^3 + 1; x^2 - 3 x + 2), ratio!(x^3; x^5 - 2 x^4 + 2 x^3 - 4 x^2 + x - 2)] {
+    ///     let pieces = r.partial_fractions("x").unwrap();
+    ///     let sum = pieces.into_iter().reduce(|a, b| a + b).unwrap();
+    ///     assert_eq!(sum, r);
+    /// }
+    /// ```
+    pub fn partial_fractions(&self, var: &str) -> Result<Vec<PolyRatio>, PolyError> {
+        let mut ratio = self.clone();
+        ratio.simplify();
+        if ratio.denominator.is_zero() {
+            return Err(PolyError::DivisionByZero { at: vec![] });
+        }
+        // Check that both parts are polynomials in var alone
+        ratio.numerator.to_coeff_vec(var)?;
+        ratio.denominator.to_coeff_vec(var)?;
+
+        let (polynomial_part, mut numerator) = ratio.numerator.div_rem(&ratio.denominator);
+        let mut denominator = ratio.denominator;
+        let mut pieces = Vec::new();
+        if !polynomial_part.is_zero() {
+            pieces.push(PolyRatio::from(polynomial_part));
+        }
+
+        let mut factors: Vec<(Polynomial, u32)> = Vec::new();
+        for factor in denominator.factor_kronecker(var) {
+            if factor.is_constant() {
+                continue;
+            }
+            match factors.iter_mut().find(|(f, _)| *f == factor) {
+                Some((_, multiplicity)) => *multiplicity += 1,
+                None => factors.push((factor, 1)),
+            }
+        }
+        for (factor, multiplicity) in factors {
+            // With the denominator f^m E, where f^m and E are coprime, s f^m + t E = 1 for some s and t, so the fraction
+            // over f^m has the numerator R = N t mod f^m, and (N - R E) / (f^m E) is what is left to split
+            let power = factor.powi(multiplicity);
+            let (rest, _) = denominator.div_rem(&power);
+            let (_, _, t) = power.extended_gcd(&rest);
+            let (_, mut remainder) = (numerator.clone() * t).div_rem(&power);
+            numerator = (numerator - remainder.clone() * rest.clone())
+                .div_rem(&power)
+                .0;
+            // Writing R in powers of f, R = c0 + c1 f + ... + c(m-1) f^(m-1), gives the pieces ck / f^(m-k)
+            let mut factor_pieces = Vec::new();
+            for k in (1..=multiplicity).rev() {
+                let (quotient, c) = remainder.div_rem(&factor);
+                if !c.is_zero() {
+                    factor_pieces.push(PolyRatio {
+                        numerator: c,
+                        denominator: factor.powi(k),
+                    });
+                }
+                remainder = quotient;
+            }
+            pieces.extend(factor_pieces.into_iter().rev());
+            denominator = rest;
+        }
+        Ok(pieces)
+    }
+
+    /// Returns the limit of the ratio as `var` grows without bound, from the degrees in `var` of the numerator and the
+    /// denominator: zero if the denominator has the higher degree, the ratio of the leading coefficients if they are the
+    /// same, and an infinity with the sign of that ratio if the numerator has the higher degree. Ratios with other
+    /// variables or with exponents that are not non-negative integers have an undefined limit.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::{coeff, LimitResult};
+    /// use sym_tfg::ratio;
+    ///
+    /// assert_eq!(ratio!(x + 1; x^2).limit_at_infinity("x"), LimitResult::Finite(coeff(0)));
+    /// assert_eq!(ratio!(3 x^2 - x; 2 x^2 + 1).limit_at_infinity("x"), LimitResult::Finite(coeff(3) / coeff(2)));
+    /// assert_eq!(ratio!(-x^3; x + 1).limit_at_infinity("x"), LimitResult::MinusInfinity);
+    /// assert_eq!(ratio!(2 x; x).limit_at_infinity("x"), LimitResult::Finite(coeff(2)));
+    /// assert_eq!(ratio!(x y; x).limit_at_infinity("x"), LimitResult::Undefined);
+    /// ```
+    pub fn limit_at_infinity(&self, var: &str) -> LimitResult {
+        let mut ratio = self.clone();
+        ratio.simplify();
+        let (numerator, denominator) = match (
+            ratio.numerator.to_coeff_vec(var),
+            ratio.denominator.to_coeff_vec(var),
+        ) {
+            (Ok(n), Ok(d)) => (n, d),
+            _ => return LimitResult::Undefined,
+        };
+        let (lead_n, lead_d) = match (numerator.last(), denominator.last()) {
+            (_, None) => return LimitResult::Undefined,
+            (None, Some(_)) => return LimitResult::Finite(Coeff::zero()),
+            (Some(n), Some(d)) => (n.clone(), d.clone()),
+        };
+        match numerator.len().cmp(&denominator.len()) {
+            Ordering::Less => LimitResult::Finite(Coeff::zero()),
+            Ordering::Equal => LimitResult::Finite(lead_n / lead_d),
+            Ordering::Greater if (lead_n / lead_d).is_positive() => LimitResult::PlusInfinity,
+            Ordering::Greater => LimitResult::MinusInfinity,
+        }
+    }
+
+    /// Finds the asymptotes of the graph of the ratio as a function of `var`. The vertical ones are at the real roots of the
+    /// denominator once the factors it shares with the numerator are cancelled, so the holes these leave are not included.
+    /// Roots of factors of the denominator of degree above 2 that do not split over the rationals cannot be found and are
+    /// left out. The horizontal asymptote is the limit at infinity when it is finite, and the oblique one is the quotient
+    /// of the numerator by the denominator when the numerator has one degree more. A ratio that simplifies to a polynomial
+    /// has none.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::coeff;
+    /// use sym_tfg::{poly, ratio};
+    ///
+    /// let a = ratio!(2 x^2 + 1; x^2 - 1).asymptotes("x").unwrap();
+    /// assert_eq!(a.vertical, vec![vec![ratio!(1; 1)], vec![ratio!(-1; 1)]]);
+    /// assert_eq!(a.horizontal, Some(coeff(2)));
+    /// assert_eq!(a.oblique, None);
+    ///
+    /// let a = ratio!(x^2 + 1; x).asymptotes("x").unwrap();
+    /// assert_eq!(a.horizontal, None);
+    /// assert_eq!(a.oblique, Some(poly!(x)));
+    ///
+    /// let a = ratio!(x^2 - 1; x - 1).asymptotes("x").unwrap();
+    /// assert!(a.vertical.is_empty() && a.horizontal.is_none() && a.oblique.is_none());
+    /// ```
+    pub fn asymptotes(&self, var: &str) -> Result<Asymptotes, PolyError> {
+        let mut ratio = self.clone();
+        ratio.simplify();
+        let numerator = ratio.numerator.to_coeff_vec(var)?;
+        let denominator = ratio.denominator.to_coeff_vec(var)?;
+        if denominator.is_empty() {
+            return Err(PolyError::DivisionByZero { at: vec![] });
+        }
+
+        // Higher degree factors without rational roots are left out
+        let (vertical, _) = real_roots(&ratio.denominator, var)?;
+
+        // The graph of a polynomial is its own line or curve, which has no asymptotes
+        let horizontal = match ratio.limit_at_infinity(var) {
+            LimitResult::Finite(c) if denominator.len() > 1 => Some(c),
+            _ => None,
+        };
+        let oblique = if denominator.len() > 1 && numerator.len() == denominator.len() + 1 {
+            Some(ratio.numerator.div_rem(&ratio.denominator).0)
+        } else {
+            None
+        };
+        Ok(Asymptotes {
+            vertical,
+            horizontal,
+            oblique,
+        })
+    }
+
+    /// Finds the rational values of `var` where the ratio is undefined, in increasing order, and whether each one is a hole
+    /// or a pole. Holes come from the factors that `simplify_recording` cancels, and poles from the denominator left.
+    /// A ratio does not remember the factors it has already cancelled, so a simplified ratio has no holes left to find.
+    /// Ratios parsed from a string or built with the operators are simplified as they are built; `ratio!` and the
+    /// struct literal keep the parts as written.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::{coeff, PolyRatio, SingularityKind};
+    /// use sym_tfg::ratio;
+    ///
+    /// let r = ratio!(x^2 + x - 2; x^2 - 4 x + 3);
+    /// assert_eq!(
+    ///     r.singularities("x"),
+    ///     vec![(coeff(1), SingularityKind::Hole), (coeff(3), SingularityKind::Pole(1))]
+    /// );
+    ///
+    /// // Parsing cancels x-1, so only the pole is left
+    /// let parsed: PolyRatio = "(x-1)*(x+2)/((x-1)*(x-3))".parse().unwrap();
+    /// assert_eq!(parsed.singularities("x"), vec![(coeff(3), SingularityKind::Pole(1))]);
+    /// ```
+    pub fn singularities(&self, var: &str) -> Vec<(Coeff, SingularityKind)> {
+        let mut ratio = self.clone();
+        let cancelled = ratio.simplify_recording();
+        let rational_roots = |p: &Polynomial| -> Vec<(Coeff, u32)> {
+            p.factor_rational(var)
+                .1
+                .into_iter()
+                .filter(|(factor, _)| factor.degree_in(var) == 1.into())
+                .filter_map(|(factor, multiplicity)| {
+                    let c = factor.to_coeff_vec(var).ok()?;
+                    Some((-c[0].clone() / c[1].clone(), multiplicity))
+                })
+                .collect()
+        };
+        let mut result: Vec<(Coeff, SingularityKind)> = rational_roots(&ratio.denominator)
+            .into_iter()
+            .map(|(root, order)| (root, SingularityKind::Pole(order)))
+            .collect();
+        for (root, _) in rational_roots(&cancelled) {
+            if !result.iter().any(|(r, _)| *r == root) {
+                result.push((root, SingularityKind::Hole));
+            }
+        }
+        result.sort_by_key(|(root, _)| root.clone());
+        result
+    }
+
+    /// Finds the values of `var` where the ratio is not defined as written, before any cancellation: the real roots of
+    /// the denominator, each one a sum of parts like the roots from `Polynomial::roots`, and 0 if `var` has a negative
+    /// power. So (x^2-1)/(x-1) excludes 1 even though it simplifies to x+1. A denominator with `var` must be a polynomial
+    /// in `var` alone, and its factors without rational roots can be at most quadratic.
+    ///
+    /// ```
+    /// use sym_tfg::ratio;
+    ///
+    /// assert_eq!(ratio!(x^2 - 1; x - 1).excluded_values("x").unwrap(), vec![vec![ratio!(1; 1)]]);
+    /// assert_eq!(ratio!(1; x^2 + 1).excluded_values("x").unwrap(), Vec::<Vec<_>>::new());
+    /// assert_eq!(ratio!(x^-1; x + 2).excluded_values("x").unwrap().len(), 2);
+    /// ```
+    pub fn excluded_values(&self, var: &str) -> Result<Vec<Vec<PolyRatio>>, PolyError> {
+        // The lowest power of var in each part, which is negative if var is in a denominator of its own
+        let lowest_power = |p: &Polynomial| -> Result<i64, PolyError> {
+            let mut lowest = 0;
+            for v in p.terms.iter().flat_map(|t| &t.variables) {
+                if v.name != var {
+                    continue;
+                }
+                if !v.degree.is_integer() {
+                    return Err(PolyError::FractionalExponent {
+                        var: v.name.clone(),
+                        degree: v.degree,
+                    });
+                }
+                lowest = lowest.min(v.degree.to_integer());
+            }
+            Ok(lowest)
+        };
+        let mut numerator = self.numerator.clone();
+        let mut denominator = self.denominator.clone();
+        numerator.simplify();
+        denominator.simplify();
+        let numerator_power = lowest_power(&numerator)?;
+        let denominator_power = lowest_power(&denominator)?;
+
+        // Clear the negative powers from the denominator, which only adds 0 as a root
+        let shift = Polynomial::monomial(Coeff::one(), &[(var, (-denominator_power).into())]);
+        let denominator = denominator * shift;
+        let (mut excluded, unsolved) = if denominator.vars().contains(var) {
+            real_roots(&denominator, var)?
+        } else {
+            (vec![], vec![])
+        };
+        if let Some(factor) = unsolved.first() {
+            return Err(PolyError::UnsupportedDegree {
+                var: var.to_string(),
+                degree: factor.degree_in(var),
+            });
+        }
+        let zero = vec![PolyRatio::zero()];
+        if numerator_power.min(denominator_power) < 0 && !excluded.contains(&zero) {
+            excluded.push(zero);
+        }
+        Ok(excluded)
+    }
+
+    /// Returns the limit of the ratio as `var` approaches `a`. Common factors are cancelled first, so a removable
+    /// singularity like x = 1 in (x^2-1)/(x-1) has the limit of what is left; any 0/0 that remains is resolved with
+    /// L'Hôpital's rule. A nonzero value over zero is an infinity when both sides go the same way, like 1/x^2 at 0,
+    /// and undefined when they do not, like 1/x at 0. Ratios with other variables have an undefined limit.
+    ///
+    /// ```
+    /// use sym_tfg::polynomial::{coeff, LimitResult};
+    /// use sym_tfg::ratio;
+    ///
+    /// assert_eq!(ratio!(x^2 - 1; x - 1).limit_at("x", coeff(1)), LimitResult::Finite(coeff(2)));
+    /// assert_eq!(ratio!(x + 1; x - 2).limit_at("x", coeff(3)), LimitResult::Finite(coeff(4)));
+    /// assert_eq!(ratio!(-1; x^2).limit_at("x", coeff(0)), LimitResult::MinusInfinity);
+    /// assert_eq!(ratio!(1; x).limit_at("x", coeff(0)), LimitResult::Undefined);
+    /// ```
+    pub fn limit_at(&self, var: &str, a: Coeff) -> LimitResult {
+        let mut ratio = self.clone();
+        ratio.simplify();
+        if ratio.numerator.to_coeff_vec(var).is_err()
+            || ratio.denominator.to_coeff_vec(var).is_err()
+            || ratio.denominator.is_zero()
+        {
+            return LimitResult::Undefined;
+        }
+        let at = [(var.to_string(), a)];
+        // Both parts are polynomials in var alone, so evaluating them can only fail by overflowing
+        let value = |p: &Polynomial| p.eval_at(&at).unwrap_or_else(|_| overflow());
+        let (mut n, mut d) = (ratio.numerator, ratio.denominator);
+
+        // Each derivative lowers the degree of the denominator, so it becomes a nonzero constant at the latest
+        while value(&d).is_zero() && value(&n).is_zero() {
+            n = n.derivative(var);
+            d = d.derivative(var);
+        }
+        let n_at_a = value(&n);
+        if !value(&d).is_zero() {
+            return LimitResult::Finite(
+                n_at_a.checked_div(&value(&d)).unwrap_or_else(|| overflow()),
+            );
+        }
+
+        // Near a, d behaves like c (var - a)^m for its first derivative c = d^(m)(a) that is not zero
+        let mut order = 0;
+        while value(&d).is_zero() {
+            d = d.derivative(var);
+            order += 1;
+        }
+        if order % 2 == 1 {
+            return LimitResult::Undefined;
+        }
+        if n_at_a.is_positive() == value(&d).is_positive() {
+            LimitResult::PlusInfinity
+        } else {
+            LimitResult::MinusInfinity
+        }
+    }
+
+    /// Cancels what the numerator and the denominator have in common, then makes the leading coefficient of the denominator
+    /// positive, so a value prints the same however it was built: (-x-1)/(-x+2) becomes (x+1)/(x-2).
+    ///
+    /// ```
+    /// use sym_tfg::ratio;
+    ///
+    /// let equivalent = [
+    ///     ratio!(-x - 1; -x + 2),
+    ///     ratio!(x^2 + 2 x + 1; x^2 - x - 2),
+    ///     ratio!(-x^2 - 2 x - 1; 2 + x - x^2),
+    /// ];
+    /// for mut r in equivalent {
+    ///     r.simplify();
+    ///     assert_eq!(r.as_string().unwrap(), "(x+1) / (x-2)");
+    /// }
+    /// ```
+    pub fn simplify(&mut self) {
+        self.simplify_recording();
+    }
 
-impl PartialEq for Polynomial {
-    fn eq(&self, other: &Self) -> bool {
-        let mut self_copy = self.clone();
-        self_copy.simplify();
-        let mut other_copy = other.clone();
-        other_copy.simplify();
-        self.terms == other.terms
+    /// Simplifies the ratio like `simplify`, but returns an error instead of panicking if a coefficient overflows, in
+    /// which case the ratio is left as it was.
+    pub fn checked_simplify(&mut self) -> Result<(), PolyError> {
+        let mut ratio = self.clone();
+        *self = catch_overflow(move || {
+            ratio.simplify();
+            ratio
+        })?;
+        Ok(())
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct PolyRatio {
-    pub numerator: Polynomial,
-    pub denominator: Polynomial,
-}
+    /// Simplifies the ratio like `simplify`, and returns the common factor it cancelled from the numerator and the
+    /// denominator, up to a constant. The roots of that factor are the holes the original ratio had, like x = 1 for
+    /// (x^2-1)/(x-1), which simplifies to x+1 and returns x-1.
+    pub fn simplify_recording(&mut self) -> Polynomial {
+        let cancelled = self.cancel();
+        self.normalize_sign();
+        cancelled
+    }
 
-impl PolyRatio {
-    pub fn simplify(&mut self) {
+    /// Multiplies both parts by -1 if the first term of the denominator, in the default order, is negative. Parts with
+    /// pending powers are left alone, since the sign cannot be taken out of a root.
+    fn normalize_sign(&mut self) {
+        if self.numerator.pending_pow != 1.into()
+            || self.denominator.pending_pow != 1.into()
+            || self.denominator.is_zero()
+        {
+            return;
+        }
+        let lead = self.denominator.leading_term_by(MonomialOrder::default());
+        if lead.coefficient < Coeff::zero() {
+            self.numerator = self.numerator.map_coefficients(|c| -c);
+            self.denominator = self.denominator.map_coefficients(|c| -c);
+        }
+    }
+
+    /// Cancels the common factors of both parts and returns their product, up to a constant.
+    fn cancel(&mut self) -> Polynomial {
         // let mut warn = false;
         // if self.numerator.degree != 1.into() {
         //     println!("Degree != 1\n{}", self.numerator.as_string());
@@ -868,21 +4557,14 @@ impl PolyRatio {
         // println!("Numerator!: {}", self.numerator.as_string());
         // println!("Denominator!: {}", self.denominator.as_string());
 
-        self.numerator.simplify();
-        self.denominator.simplify();
+        self.numerator.apply_pow();
+        self.denominator.apply_pow();
 
         // println!("Numerator: {}", self.numerator.as_string());
         // println!("Denominator: {}", self.denominator.as_string());
 
-        // Make the coefficients integers
         let mut n = self.numerator.clone();
         let mut d = self.denominator.clone();
-        // if warn {
-        //     println!("Numerator: {}", n.as_string());
-        //     println!("Denominator: {}", d.as_string());
-        // }
-        let adjust_n = n.make_integer();
-        let adjust_d = d.make_integer();
 
         // Find the smallest negative exponent of each variable in the denominator
         let mut vars_to_move: Vec<Variable> = vec![];
@@ -898,20 +4580,12 @@ impl PolyRatio {
         }
 
         // Multiply the numerator and denominator by the accumulated terms
-        n = n * Polynomial {
-            terms: vec![Term {
-                coefficient: Rational64::new(1, 1),
-                variables: vars_to_move.clone(),
-            }],
-            degree: 1.into(),
-        };
-        d = d * Polynomial {
-            terms: vec![Term {
-                coefficient: Rational64::new(1, 1),
-                variables: vars_to_move.clone(),
-            }],
-            degree: 1.into(),
-        };
+        let factor: Vec<(&str, Rational64)> = vars_to_move
+            .iter()
+            .map(|v| (v.name.as_str(), v.degree))
+            .collect();
+        n *= Polynomial::monomial(Coeff::one(), &factor);
+        d *= Polynomial::monomial(Coeff::one(), &factor);
 
         // println!("Numer: {}", n.as_string());
         // println!("Denom: {}", d.as_string());
@@ -930,24 +4604,20 @@ impl PolyRatio {
         }
 
         // Multiply the numerator and denominator by the accumulated terms
-        n = n * Polynomial {
-            terms: vec![Term {
-                coefficient: Rational64::new(1, 1),
-                variables: vars_to_move.clone(),
-            }],
-            degree: 1.into(),
-        };
-        d = d * Polynomial {
-            terms: vec![Term {
-                coefficient: Rational64::new(1, 1),
-                variables: vars_to_move.clone(),
-            }],
-            degree: 1.into(),
-        };
+        let factor: Vec<(&str, Rational64)> = vars_to_move
+            .iter()
+            .map(|v| (v.name.as_str(), v.degree))
+            .collect();
+        n *= Polynomial::monomial(Coeff::one(), &factor);
+        d *= Polynomial::monomial(Coeff::one(), &factor);
 
         // println!("Numer: {}", n.as_string());
         // println!("Denom: {}", d.as_string());
 
+        // Take the contents out, so the rest has integer coefficients without common factors
+        let (content_n, mut n) = n.primitive_part();
+        let (content_d, mut d) = d.primitive_part();
+
         // Factor out as much as possible from the numerator and denominator
         let (t1, mut n) = n.factor();
         let (t2, mut d) = d.factor();
@@ -971,10 +4641,7 @@ impl PolyRatio {
 
         let gcd_term = Term {
             // The term that will be canceled out in the numerator and denominator
-            coefficient: Rational64::from_integer(num_integer::gcd(
-                t1.coefficient.numer().abs(),
-                t2.coefficient.numer().abs(),
-            )),
+            coefficient: Coeff::one(),
             variables: if var_name != "" {
                 // If the terms share a variable
                 vec![Variable {
@@ -986,42 +4653,43 @@ impl PolyRatio {
             },
         };
         // println!("GCD: {:?}", gcd_term);
+        let mut cancelled = Polynomial::builder()
+            .push(gcd_term.clone())
+            .build()
+            .unwrap();
 
-        n = n * Polynomial {
-            terms: vec![t1.clone()],
-            degree: 1.into(),
-        };
+        // Pushing terms that are already built cannot fail
+        n *= Polynomial::builder().push(t1.clone()).build().unwrap();
         // if warn {
         //     println!("n: {:?}", n);
         //     println!("t1: {:?}", t1.clone());
         //     println!("{:?}", vars_to_move);
         // }
-        d = d * Polynomial {
-            terms: vec![t2],
-            degree: 1.into(),
-        };
+        d *= Polynomial::builder().push(t2).build().unwrap();
 
         // Cancel out the gcd from the numerator and denominator
         let mut inv = gcd_term.clone();
         inv.invert();
         // println!("Inv: {:?}", inv);
         // println!("Numerator: {}", n.as_string());
-        n = n * Polynomial {
-            terms: vec![inv.clone()],
-            degree: 1.into(),
-        };
-        d = d * Polynomial {
-            terms: vec![inv],
-            degree: 1.into(),
-        };
+        n *= Polynomial::builder().push(inv.clone()).build().unwrap();
+        d *= Polynomial::builder().push(inv).build().unwrap();
 
-        // Undo the scaling of the coefficients
-        for term in &mut n.terms {
-            term.coefficient *= Rational64::new(1, adjust_n);
-        }
-        for term in &mut d.terms {
-            term.coefficient *= Rational64::new(1, adjust_d);
-        }
+        // Put back what is left of the contents once they cancel out, as integers
+        let (scale_n, scale_d) =
+            if !content_d.is_zero() && n.pending_pow == 1.into() && d.pending_pow == 1.into() {
+                let ratio = content_n
+                    .checked_div(&content_d)
+                    .unwrap_or_else(|| overflow());
+                (
+                    Coeff::from(ratio.numer().clone()),
+                    Coeff::from(ratio.denom().clone()),
+                )
+            } else {
+                (content_n, content_d)
+            };
+        let n = n.map_coefficients(|c| c.try_mul(&scale_n).unwrap_or_else(|| overflow()));
+        let d = d.map_coefficients(|c| c.try_mul(&scale_d).unwrap_or_else(|| overflow()));
 
         // if warn && self.numerator.degree == 1.into() {
         //     println!("Warning: Degree != 1");
@@ -1032,181 +4700,658 @@ impl PolyRatio {
         self.numerator.simplify();
         self.denominator.simplify();
 
-        if self.denominator == self.numerator {
-            self.numerator = Polynomial {
-                terms: vec![Term {
-                    coefficient: Rational64::new(1, 1),
-                    variables: vec![],
-                }],
-                degree: 1.into(),
-            };
-            self.denominator = Polynomial {
-                terms: vec![Term {
-                    coefficient: Rational64::new(1, 1),
-                    variables: vec![],
-                }],
-                degree: 1.into(),
-            };
+        if self.numerator.pending_pow != 1.into() || self.denominator.pending_pow != 1.into() {
+            // Roots that do not simplify can still cancel out when they are the same
+            if self.denominator == self.numerator {
+                cancelled *= self.numerator.clone();
+                *self = PolyRatio::one();
+            }
+            return cancelled;
+        }
+
+        // Cancel the common factors that are not monomials, like x-1 in (x^2-1)/(x-1)
+        let gcd = self.numerator.gcd(&self.denominator);
+        if gcd.terms.iter().any(|t| !t.variables.is_empty()) {
+            let (n, n_rem) = self.numerator.div_rem(&gcd);
+            let (d, d_rem) = self.denominator.div_rem(&gcd);
+            if n_rem.is_zero() && d_rem.is_zero() {
+                self.numerator = n;
+                self.denominator = d;
+                cancelled *= gcd;
+            }
+        }
+
+        // With several variables the gcd is only a monomial, so at least cancel one side when it divides the other
+        if !self.denominator.is_constant() && !self.numerator.is_constant() {
+            let order = MonomialOrder::default();
+            let (mut q, r) = self
+                .numerator
+                .reduce(std::slice::from_ref(&self.denominator), order);
+            if r.is_zero() {
+                self.numerator = q.remove(0);
+                cancelled *= std::mem::replace(&mut self.denominator, Polynomial::one());
+                return cancelled;
+            }
+            let (mut q, r) = self
+                .denominator
+                .reduce(std::slice::from_ref(&self.numerator), order);
+            if r.is_zero() {
+                cancelled *= std::mem::replace(&mut self.numerator, Polynomial::one());
+                self.denominator = q.remove(0);
+            }
         }
+        cancelled
     }
 
-    pub fn as_string(&self) -> String {
-        if self.denominator.as_string() == "1".to_string() {
-            self.numerator.as_string()
-        } else if self.denominator.as_string() == "0".to_string() {
-            "ERROR: Division by zero!".to_string()
+    /// Returns the least common multiple of both denominators, along with the polynomials that each denominator has to be multiplied by to reach it.
+    /// Falls back to the product of the denominators when the least common multiple cannot be found exactly.
+    fn common_denominator(&self, other: &PolyRatio) -> (Polynomial, Polynomial, Polynomial) {
+        let (d1, d2) = (&self.denominator, &other.denominator);
+        if d1.pending_pow == 1.into()
+            && d2.pending_pow == 1.into()
+            && !d1.is_zero()
+            && !d2.is_zero()
+        {
+            let lcm = d1.lcm(d2);
+            let (f1, r1) = lcm.div_rem(d1);
+            let (f2, r2) = lcm.div_rem(d2);
+            if r1.is_zero() && r2.is_zero() {
+                return (lcm, f1, f2);
+            }
+        }
+        (d1.clone() * d2.clone(), d2.clone(), d1.clone())
+    }
+
+    /// Raises the ratio to an integer power, raising the numerator and the denominator with `Polynomial::expand_pow`,
+    /// or by squaring the ratio for exponents too large for it. Negative exponents invert the ratio.
+    pub fn pow(&self, exp: i64) -> PolyRatio {
+        let mut base = if exp < 0 {
+            PolyRatio {
+                numerator: self.denominator.clone(),
+                denominator: self.numerator.clone(),
+            }
         } else {
-            format!(
-                "({}) / ({})",
-                self.numerator.as_string(),
-                self.denominator.as_string()
-            )
+            self.clone()
+        };
+        if let Ok(n) = u32::try_from(exp.unsigned_abs()) {
+            let mut result = PolyRatio {
+                numerator: base.numerator.expand_pow(n),
+                denominator: base.denominator.expand_pow(n),
+            };
+            result.simplify();
+            return result;
+        }
+        let mut result = PolyRatio::from(Polynomial {
+            terms: vec![Term {
+                coefficient: Coeff::one(),
+                variables: vec![],
+            }],
+            pending_pow: 1.into(),
+        });
+        let mut n = exp.unsigned_abs();
+        while n > 0 {
+            if n % 2 == 1 {
+                result *= base.clone();
+            }
+            n /= 2;
+            if n > 0 {
+                base = base.clone() * base;
+            }
+        }
+        result
+    }
+
+    /// Replaces the variables that have a value with it in both parts, like `Polynomial::evaluate`. The ratio is left
+    /// unchanged on error.
+    pub fn evaluate(&mut self, values: &[(String, Coeff)]) -> Result<(), PolyError> {
+        let mut numerator = self.numerator.clone();
+        numerator.evaluate(values)?;
+        self.denominator.evaluate(values)?;
+        self.numerator = numerator;
+        Ok(())
+    }
+
+    /// Replaces every occurrence of the variable `var` with the polynomial `value` in the numerator and the denominator,
+    /// then simplifies the ratio. Unlike `Polynomial::substitute`, negative powers of `var` can be replaced by any value,
+    /// since they go to the denominator. Values that make the denominator zero are an error.
+    pub fn substitute(&self, var: &str, value: &Polynomial) -> Result<PolyRatio, PolyError> {
+        self.substitute_ratio(var, &PolyRatio::from(value.clone()))
+    }
+
+    /// Replaces every occurrence of the variable `var` with the ratio `value`, like `substitute`.
+    pub fn substitute_ratio(&self, var: &str, value: &PolyRatio) -> Result<PolyRatio, PolyError> {
+        let numerator = substitute_part(&self.numerator, var, value)?;
+        let denominator = substitute_part(&self.denominator, var, value)?;
+        let mut result = numerator / denominator;
+        result.simplify();
+        if result.denominator.is_zero() {
+            let mut at = vec![];
+            if value.numerator.is_constant() && value.denominator.is_constant() {
+                at.push((var.to_string(), value.eval_at(&[])?));
+            }
+            return Err(PolyError::DivisionByZero { at });
+        }
+        Ok(result)
+    }
+
+    /// Renames the variable `from` to `to` in the numerator and the denominator, then simplifies the ratio.
+    ///
+    /// ```
+    /// use sym_tfg::ratio;
+    ///
+    /// let mut r = ratio!(x y; y^2 + y);
+    /// r.rename_variable("y", "x");
+    /// assert_eq!(r, ratio!(x; x + 1));
+    /// ```
+    pub fn rename_variable(&mut self, from: &str, to: &str) {
+        if from == to {
+            return;
+        }
+        self.numerator.rename_variable(from, to);
+        self.denominator.rename_variable(from, to);
+        self.simplify();
+    }
+
+    /// Evaluates the ratio exactly, with `Polynomial::eval_at` on both parts. Values that make the denominator zero are an
+    /// error naming the values given to the variables of the ratio.
+    pub fn eval_at(&self, values: &[(String, Coeff)]) -> Result<Coeff, PolyError> {
+        let denominator = self.denominator.eval_at(values)?;
+        if denominator.is_zero() {
+            let mut vars = self.numerator.vars();
+            vars.extend(self.denominator.vars());
+            return Err(PolyError::DivisionByZero {
+                at: values
+                    .iter()
+                    .filter(|(var, _)| vars.contains(var))
+                    .cloned()
+                    .collect(),
+            });
         }
+        Ok(self.numerator.eval_at(values)? / denominator)
     }
 
-    pub fn evaluate(&mut self, values: &Vec<(String, Rational64)>) {
-        self.numerator.evaluate(values);
-        self.denominator.evaluate(values);
+    /// Evaluates the ratio approximately with floating point numbers. Returns `None` if a variable has no value or the denominator is zero.
+    ///
+    /// ```
+    /// use sym_tfg::ratio;
+    ///
+    /// let r = ratio!(x^2 + 1; x - 2);
+    /// assert_eq!(r.evaluate_f64(&[("x", 3.0)]), Some(10.0));
+    /// assert_eq!(r.evaluate_f64(&[("x", 2.0)]), None);
+    /// ```
+    pub fn evaluate_f64(&self, values: &[(&str, f64)]) -> Option<f64> {
+        let denominator = self.denominator.evaluate_f64(values)?;
+        if denominator == 0.0 {
+            return None;
+        }
+        Some(self.numerator.evaluate_f64(values)? / denominator)
     }
 }
 
-impl Add for PolyRatio {
-    type Output = Self;
+/// Finds the distinct real roots of a polynomial in `var` alone, each one a sum of parts like the roots from
+/// `Polynomial::roots`: the rational ones from `factor_rational`, and the irrational ones of the quadratic factors left.
+/// Also returns the factors of higher degree, whose roots cannot be found.
+fn real_roots(
+    p: &Polynomial,
+    var: &str,
+) -> Result<(Vec<Vec<PolyRatio>>, Vec<Polynomial>), PolyError> {
+    p.to_coeff_vec(var)?;
+    let mut roots = Vec::new();
+    let mut unsolved = Vec::new();
+    for (factor, _) in p.factor_rational(var).1 {
+        match factor.degree_in(var).to_integer() {
+            0 => {}
+            1 => {
+                let c = factor.to_coeff_vec(var)?;
+                let root = Polynomial::constant(-c[0].clone() / c[1].clone());
+                roots.push(vec![PolyRatio::from(root)]);
+            }
+            // A quadratic with a negative discriminant has no real roots
+            2 => {
+                if !factor.discriminant(var)?.eval_at(&[])?.is_negative() {
+                    roots.extend(factor.roots(var)?.into_iter().filter(|r| !r.is_empty()));
+                }
+            }
+            _ => unsolved.push(factor),
+        }
+    }
+    Ok((roots, unsolved))
+}
 
-    fn add(self, other: Self) -> Self {
-        let mut result = PolyRatio {
-            numerator: self.numerator.clone() * other.denominator.clone()
-                + other.numerator.clone() * self.denominator.clone(),
-            denominator: self.denominator.clone() * other.denominator.clone(),
+/// Replaces `var` with the ratio `value` in one part of a ratio, term by term, raising `value` to each power of `var`.
+fn substitute_part(p: &Polynomial, var: &str, value: &PolyRatio) -> Result<PolyRatio, PolyError> {
+    if !p.variables().iter().any(|name| name == var) {
+        return Ok(PolyRatio::from(p.clone()));
+    }
+    let mut p = p.clone();
+    p.apply_pow();
+    if p.pending_pow != 1.into() {
+        return Err(PolyError::FractionalExponent {
+            var: format!("({})", p.as_string()),
+            degree: p.pending_pow,
+        });
+    }
+    let mut result = PolyRatio::zero();
+    for term in &p.terms {
+        let mut rest = Term {
+            coefficient: term.coefficient.clone(),
+            variables: vec![],
         };
-        result.simplify();
-        result
+        let mut replaced = PolyRatio::one();
+        for v in &term.variables {
+            if v.name != var {
+                rest.variables.push(v.clone());
+                continue;
+            }
+            if !v.degree.is_integer() {
+                return Err(PolyError::FractionalExponent {
+                    var: v.name.clone(),
+                    degree: v.degree,
+                });
+            }
+            replaced *= value.pow(v.degree.to_integer());
+        }
+        result += replaced * PolyRatio::from(Polynomial::builder().push(rest).build()?);
     }
+    Ok(result)
 }
 
-impl Sub for PolyRatio {
+impl RatioArithmetic for Coeff {
+    fn common_denominator(a: &PolyRatio, b: &PolyRatio) -> (Polynomial, Polynomial, Polynomial) {
+        a.common_denominator(b)
+    }
+
+    fn simplify_ratio(ratio: &mut PolyRatio) {
+        ratio.simplify();
+    }
+}
+
+impl<T: RatioArithmetic> Add for PolyRatio<T> {
     type Output = Self;
 
-    fn sub(self, other: Self) -> Self {
-        let mut result = PolyRatio {
-            numerator: self.numerator.clone() * other.denominator.clone()
-                - other.numerator.clone() * self.denominator.clone(),
-            denominator: self.denominator.clone() * other.denominator.clone(),
-        };
-        result.simplify();
-        result
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
     }
 }
 
-impl Mul for PolyRatio {
+impl<T: RatioArithmetic> Sub for PolyRatio<T> {
     type Output = Self;
 
-    fn mul(self, other: Self) -> Self {
-        let mut result = PolyRatio {
-            numerator: self.numerator.clone() * other.numerator.clone(),
-            denominator: self.denominator.clone() * other.denominator.clone(),
-        };
-        result.simplify();
-        result
+    fn sub(mut self, other: Self) -> Self {
+        self -= other;
+        self
     }
 }
 
-impl Div for PolyRatio {
+/// Negates the numerator, or the denominator if only the numerator is raised to a pending power like 1/2,
+/// since the sign cannot go inside a square root.
+///
+/// ```
+/// use sym_tfg::ratio;
+///
+/// let r = ratio!(x + 1; x - 2);
+/// assert_eq!((-r.clone()).as_string().unwrap(), "(-x-1) / (x-2)");
+/// assert_eq!(-(-r.clone()), r);
+/// ```
+impl<T: Coefficient> Neg for PolyRatio<T> {
     type Output = Self;
 
-    fn div(self, other: Self) -> Self {
-        let mut result = PolyRatio {
-            numerator: self.numerator.clone() * other.denominator.clone(),
-            denominator: self.denominator.clone() * other.numerator.clone(),
-        };
-        result.simplify();
-        result
+    fn neg(mut self) -> Self {
+        if self.numerator.pending_pow != 1.into() && self.denominator.pending_pow == 1.into() {
+            self.denominator = -self.denominator;
+        } else {
+            self.numerator = -self.numerator;
+        }
+        self
+    }
+}
+
+impl<T: RatioArithmetic> Mul for PolyRatio<T> {
+    type Output = Self;
+
+    fn mul(mut self, other: Self) -> Self {
+        self *= other;
+        self
+    }
+}
+
+/// Dividing by zero does not panic: it gives a ratio with a zero denominator, see `PolyRatio::new`.
+impl<T: RatioArithmetic> Div for PolyRatio<T> {
+    type Output = Self;
+
+    fn div(mut self, other: Self) -> Self {
+        self /= other;
+        self
+    }
+}
+
+/// Two ratios are equal if cross-multiplying them gives the same polynomial, so (2x)/(4) equals (x)/(2) and
+/// (x^2-1)/(x-1) equals x+1. Ratios with pending powers like 1/2 can only be compared part by part.
+/// Ratios with a zero denominator are all equal to each other and to nothing else, which keeps the comparison transitive.
+impl<T: Coefficient> PartialEq for PolyRatio<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.denominator.is_zero(), other.denominator.is_zero()) {
+            (true, true) => return true,
+            (true, false) | (false, true) => return false,
+            (false, false) => {}
+        }
+        let parts = [
+            &self.numerator,
+            &self.denominator,
+            &other.numerator,
+            &other.denominator,
+        ];
+        if parts.iter().any(|p| p.pending_pow != 1.into()) {
+            return self.numerator.pending_pow == other.numerator.pending_pow
+                && self.denominator.pending_pow == other.denominator.pending_pow
+                && self.numerator == other.numerator
+                && self.denominator == other.denominator;
+        }
+        (self.numerator.clone() * other.denominator.clone()
+            - other.numerator.clone() * self.denominator.clone())
+        .is_zero()
+    }
+}
+
+impl<T: Coefficient + Eq> Eq for PolyRatio<T> {}
+
+impl<T: RatioArithmetic> Zero for PolyRatio<T> {
+    fn zero() -> Self {
+        PolyRatio::from(Polynomial::zero())
+    }
+
+    /// True if the numerator is zero and the denominator is not.
+    fn is_zero(&self) -> bool {
+        self.numerator.is_zero() && !self.denominator.is_zero()
     }
 }
 
-impl From<Polynomial> for PolyRatio {
-    fn from(p: Polynomial) -> Self {
+impl<T: RatioArithmetic> One for PolyRatio<T> {
+    fn one() -> Self {
+        PolyRatio::from(Polynomial::one())
+    }
+
+    fn is_one(&self) -> bool {
+        let mut r = self.clone();
+        T::simplify_ratio(&mut r);
+        r.numerator.is_one() && r.denominator.is_one()
+    }
+}
+
+/// The assignment operators give the same results as the binary ones.
+///
+/// ```
+/// use sym_tfg::ratio;
+///
+/// let (a, b) = (ratio!(x^2 + 1; x - 2), ratio!(x; x + 1));
+/// let mut r = a.clone();
+/// r += b.clone();
+/// assert_eq!(r, a.clone() + b.clone());
+/// r *= b.clone();
+/// assert_eq!(r, (a.clone() + b.clone()) * b.clone());
+/// r /= b.clone();
+/// assert_eq!(r, a.clone() + b.clone());
+/// r -= a.clone();
+/// assert_eq!(r, b);
+/// ```
+impl<T: RatioArithmetic> AddAssign for PolyRatio<T> {
+    fn add_assign(&mut self, other: Self) {
+        let (denominator, self_factor, other_factor) = T::common_denominator(self, &other);
+        self.numerator *= self_factor;
+        self.numerator += other.numerator * other_factor;
+        self.denominator = denominator;
+        T::simplify_ratio(self);
+    }
+}
+
+/// ```
+/// use num::Zero;
+/// use sym_tfg::ratio;
+///
+/// let mut r = ratio!(x^2 + 1; x - 2);
+/// r -= r.clone();
+/// assert!(r.is_zero());
+/// ```
+impl<T: RatioArithmetic> SubAssign for PolyRatio<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self += -other;
+    }
+}
+
+impl<T: RatioArithmetic> MulAssign for PolyRatio<T> {
+    fn mul_assign(&mut self, other: Self) {
+        self.numerator *= other.numerator;
+        self.denominator *= other.denominator;
+        T::simplify_ratio(self);
+    }
+}
+
+/// Dividing by zero leaves a zero denominator, like `Div`.
+impl<T: RatioArithmetic> DivAssign for PolyRatio<T> {
+    fn div_assign(&mut self, other: Self) {
+        self.numerator *= other.denominator;
+        self.denominator *= other.numerator;
+        T::simplify_ratio(self);
+    }
+}
+
+impl<T: Coefficient> From<Polynomial<T>> for PolyRatio<T> {
+    fn from(p: Polynomial<T>) -> Self {
         PolyRatio {
             numerator: p,
-            denominator: Polynomial {
-                terms: vec![Term {
-                    coefficient: Rational64::new(1, 1),
-                    variables: vec![],
-                }],
-                degree: 1.into(),
-            },
+            denominator: Polynomial::one(),
         }
     }
 }
 
-impl Add<PolyRatio> for Polynomial {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Add<PolyRatio<T>> for Polynomial<T> {
+    type Output = PolyRatio<T>;
 
-    fn add(self, other: PolyRatio) -> PolyRatio {
+    fn add(self, other: PolyRatio<T>) -> PolyRatio<T> {
         let upgraded_self = PolyRatio::from(self);
         upgraded_self + other
     }
 }
 
-impl Sub<PolyRatio> for Polynomial {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Sub<PolyRatio<T>> for Polynomial<T> {
+    type Output = PolyRatio<T>;
 
-    fn sub(self, other: PolyRatio) -> PolyRatio {
+    fn sub(self, other: PolyRatio<T>) -> PolyRatio<T> {
         let upgraded_self = PolyRatio::from(self);
         upgraded_self - other
     }
 }
 
-impl Mul<PolyRatio> for Polynomial {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Mul<PolyRatio<T>> for Polynomial<T> {
+    type Output = PolyRatio<T>;
 
-    fn mul(self, other: PolyRatio) -> PolyRatio {
+    fn mul(self, other: PolyRatio<T>) -> PolyRatio<T> {
         let upgraded_self = PolyRatio::from(self);
         upgraded_self * other
     }
 }
 
-impl Div<PolyRatio> for Polynomial {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Div<PolyRatio<T>> for Polynomial<T> {
+    type Output = PolyRatio<T>;
 
-    fn div(self, other: PolyRatio) -> PolyRatio {
+    fn div(self, other: PolyRatio<T>) -> PolyRatio<T> {
         let upgraded_self = PolyRatio::from(self);
         upgraded_self / other
     }
 }
 
-impl Add<Polynomial> for PolyRatio {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Add<Polynomial<T>> for PolyRatio<T> {
+    type Output = PolyRatio<T>;
 
-    fn add(self, other: Polynomial) -> PolyRatio {
+    fn add(self, other: Polynomial<T>) -> PolyRatio<T> {
         let upgraded_other = PolyRatio::from(other);
         self + upgraded_other
     }
 }
 
-impl Sub<Polynomial> for PolyRatio {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Sub<Polynomial<T>> for PolyRatio<T> {
+    type Output = PolyRatio<T>;
 
-    fn sub(self, other: Polynomial) -> PolyRatio {
+    fn sub(self, other: Polynomial<T>) -> PolyRatio<T> {
         let upgraded_other = PolyRatio::from(other);
         self - upgraded_other
     }
 }
 
-impl Mul<Polynomial> for PolyRatio {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Mul<Polynomial<T>> for PolyRatio<T> {
+    type Output = PolyRatio<T>;
 
-    fn mul(self, other: Polynomial) -> PolyRatio {
+    fn mul(self, other: Polynomial<T>) -> PolyRatio<T> {
         let upgraded_other = PolyRatio::from(other);
         self * upgraded_other
     }
 }
 
-impl Div<Polynomial> for PolyRatio {
-    type Output = PolyRatio;
+impl<T: RatioArithmetic> Div<Polynomial<T>> for PolyRatio<T> {
+    type Output = PolyRatio<T>;
 
-    fn div(self, other: Polynomial) -> PolyRatio {
+    fn div(self, other: Polynomial<T>) -> PolyRatio<T> {
         let upgraded_other = PolyRatio::from(other);
         self / upgraded_other
     }
 }
+
+/// Implements the arithmetic between polynomials or ratios and plain numbers, in both operand orders,
+/// by turning the number into a constant polynomial.
+macro_rules! impl_scalar_ops {
+    ($scalar:ty, $to_coeff:path) => {
+        impl Add<$scalar> for Polynomial {
+            type Output = Polynomial;
+
+            fn add(self, other: $scalar) -> Polynomial {
+                self + Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Add<Polynomial> for $scalar {
+            type Output = Polynomial;
+
+            fn add(self, other: Polynomial) -> Polynomial {
+                Polynomial::constant($to_coeff(self)) + other
+            }
+        }
+
+        impl Sub<$scalar> for Polynomial {
+            type Output = Polynomial;
+
+            fn sub(self, other: $scalar) -> Polynomial {
+                self - Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Sub<Polynomial> for $scalar {
+            type Output = Polynomial;
+
+            fn sub(self, other: Polynomial) -> Polynomial {
+                Polynomial::constant($to_coeff(self)) - other
+            }
+        }
+
+        impl Mul<$scalar> for Polynomial {
+            type Output = Polynomial;
+
+            fn mul(self, other: $scalar) -> Polynomial {
+                self * Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Mul<Polynomial> for $scalar {
+            type Output = Polynomial;
+
+            fn mul(self, other: Polynomial) -> Polynomial {
+                Polynomial::constant($to_coeff(self)) * other
+            }
+        }
+
+        /// Dividing by the number 0 gives a ratio with a zero denominator.
+        impl Div<$scalar> for Polynomial {
+            type Output = PolyRatio;
+
+            fn div(self, other: $scalar) -> PolyRatio {
+                PolyRatio::from(self) / Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Div<Polynomial> for $scalar {
+            type Output = PolyRatio;
+
+            fn div(self, other: Polynomial) -> PolyRatio {
+                PolyRatio::from(Polynomial::constant($to_coeff(self))) / other
+            }
+        }
+
+        impl Add<$scalar> for PolyRatio {
+            type Output = PolyRatio;
+
+            fn add(self, other: $scalar) -> PolyRatio {
+                self + Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Add<PolyRatio> for $scalar {
+            type Output = PolyRatio;
+
+            fn add(self, other: PolyRatio) -> PolyRatio {
+                Polynomial::constant($to_coeff(self)) + other
+            }
+        }
+
+        impl Sub<$scalar> for PolyRatio {
+            type Output = PolyRatio;
+
+            fn sub(self, other: $scalar) -> PolyRatio {
+                self - Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Sub<PolyRatio> for $scalar {
+            type Output = PolyRatio;
+
+            fn sub(self, other: PolyRatio) -> PolyRatio {
+                Polynomial::constant($to_coeff(self)) - other
+            }
+        }
+
+        impl Mul<$scalar> for PolyRatio {
+            type Output = PolyRatio;
+
+            fn mul(self, other: $scalar) -> PolyRatio {
+                self * Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Mul<PolyRatio> for $scalar {
+            type Output = PolyRatio;
+
+            fn mul(self, other: PolyRatio) -> PolyRatio {
+                Polynomial::constant($to_coeff(self)) * other
+            }
+        }
+
+        /// Dividing by the number 0 gives a ratio with a zero denominator.
+        impl Div<$scalar> for PolyRatio {
+            type Output = PolyRatio;
+
+            fn div(self, other: $scalar) -> PolyRatio {
+                self / Polynomial::constant($to_coeff(other))
+            }
+        }
+
+        impl Div<PolyRatio> for $scalar {
+            type Output = PolyRatio;
+
+            fn div(self, other: PolyRatio) -> PolyRatio {
+                Polynomial::constant($to_coeff(self)) / other
+            }
+        }
+    };
+}
+
+impl_scalar_ops!(Coeff, Coeff::from);
+impl_scalar_ops!(i64, coeff);
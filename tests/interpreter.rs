@@ -0,0 +1,251 @@
+//! Runs the interpreter on small inputs and checks what it prints.
+
+use std::fs;
+use std::process::{Command, Output};
+
+/// Runs the interpreter with `args` on `input`, written to `input.txt` in a directory of its own named after `test`.
+fn run(test: &str, input: &str, args: &[&str]) -> Output {
+    let dir = std::env::temp_dir().join(format!("sym_tfg_{}_{}", std::process::id(), test));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("input.txt"), input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_sym_tfg"))
+        .args(args)
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// 2^62 fits in an i64 but twice it does not, which must be reported on its line without stopping the run.
+#[cfg(not(feature = "bigrational"))]
+#[test]
+fn coefficient_overflow_near_i64_max() {
+    let output = run(
+        "coefficient_overflow",
+        "y = 4611686018427387904\n2y\ny - 1\n",
+        &["--keep-going"],
+    );
+    assert_eq!(
+        stderr(&output),
+        "\tError: a number is too large to fit in 64 bits on line 2 of input.txt\n"
+    );
+    assert!(stdout(&output).ends_with("y - 1\n\t4611686018427387903\n"));
+}
+
+/// The input that overflows 64-bit coefficients above, which arbitrary precision ones hold exactly.
+#[cfg(feature = "bigrational")]
+#[test]
+fn big_coefficients_do_not_overflow() {
+    let output = run(
+        "big_coefficients",
+        "y = 4611686018427387904\n2y\ny*y\nw = 1/7\nw^3\n",
+        &[],
+    );
+    assert!(output.status.success());
+    assert_eq!(stderr(&output), "");
+    assert!(stdout(&output).contains("2y\n\t9223372036854775808\n"));
+    assert!(stdout(&output).contains("y*y\n\t21267647932558653966460912964485513216\n"));
+    assert!(stdout(&output).ends_with("w^3\n\t1/343\n"));
+}
+
+/// A name next to parentheses is a call only if it is defined as a function, and multiplies the group otherwise.
+#[test]
+fn calls_and_implicit_multiplication() {
+    let output = run(
+        "calls",
+        "x(x-3)\nx (x-3)\nf(x) = x^2 + 1\nf(3)\nf(y)(y-1)\nh(1, 2)\n",
+        &["--keep-going"],
+    );
+    assert_eq!(
+        stdout(&output),
+        "x(x-3)\n\tx^(2)-3x\nx (x-3)\n\tx^(2)-3x\nf(x) = x^2 + 1\n\tf(x) = x^(2)+1\nf(3)\n\t10\n\
+         f(y)(y-1)\n\ty^(3)-y^(2)+y-1\nh(1, 2)\n"
+    );
+    assert_eq!(stderr(&output), "\tError: undefined function h\n");
+}
+
+/// A function whose body calls it is rejected, whether or not it was defined before.
+#[test]
+fn recursive_definitions() {
+    let output = run(
+        "recursive",
+        "g(x) = g(x)\nf(x) = x^2\nf(x) = f(x) + 1\nf(2)\n",
+        &["--keep-going"],
+    );
+    assert_eq!(
+        stderr(&output),
+        "\tError: recursive definition of g\n\tError: recursive definition of f\n"
+    );
+    assert!(stdout(&output).ends_with("f(2)\n\t4\n"));
+}
+
+/// A line that does not parse stops the run before anything is run, pointing at where it went wrong.
+#[test]
+fn parse_error_is_located() {
+    let output = run("parse_error", "x + 1\nx +* 2\n3\n", &[]);
+    assert!(!output.status.success());
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).starts_with(" --> input.txt:2:4\n"));
+    assert!(stderr(&output).contains("2 | x +* 2\n"));
+}
+
+/// Skipping lines that fail to parse or to run still ends the run with an error status.
+#[test]
+fn failed_lines_give_an_error_status() {
+    let output = run("keep_going", "x + 1\nx +* 2\n3\n", &["--keep-going"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).starts_with(" --> input.txt:2:4\n"));
+    assert!(stdout(&output).ends_with("3\n\t3\n"));
+
+    let output = run("runtime_error", "x + 1\n1/0\n3\n", &[]);
+    assert!(!output.status.success());
+    assert_eq!(
+        stderr(&output),
+        "\tError: division by zero on line 2 of input.txt\n"
+    );
+    assert!(stdout(&output).ends_with("3\n\t3\n"));
+
+    assert!(run("no_error", "x + 1\n3\n", &[]).status.success());
+}
+
+/// Greek letters are variable names like any other, printed back unchanged.
+#[test]
+fn greek_variable_names() {
+    let output = run("greek", "α*t^2 + β*t + γ\nα = 3\n2*α\n", &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "α*t^2 + β*t + γ\n\tt^(2)α+tβ+γ\nα = 3\n\tα = 3\n2*α\n\t6\n"
+    );
+}
+
+/// Each line builds on the result of the one before it through `ans`, which is an error before there is any result.
+#[test]
+fn ans_is_the_previous_result() {
+    let output = run(
+        "ans",
+        "ans + 1\nx + 1\nans * (x - 1)\nans - x^2\n",
+        &["--keep-going"],
+    );
+    assert_eq!(
+        stderr(&output),
+        "\tError: there is no previous result to use as ans\n"
+    );
+    assert!(stdout(&output).ends_with("x + 1\n\tx+1\nans * (x - 1)\n\tx^(2)-1\nans - x^2\n\t-1\n"));
+}
+
+/// A bound variable is evaluated until it is unset or all bindings are cleared, and unsetting an unbound one only warns.
+#[test]
+fn unset_and_clear() {
+    let output = run(
+        "unset",
+        "x = 3\nx^2 + 1\nunset x\nx^2 + 1\nunset y\nx = 2\ny = 5\nx + y\nclear\nx + y\n",
+        &[],
+    );
+    assert!(output.status.success());
+    assert_eq!(stderr(&output), "\tWarning: y is not bound\n");
+    assert_eq!(
+        stdout(&output),
+        "x = 3\n\tx = 3\nx^2 + 1\n\t10\nunset x\nx^2 + 1\n\tx^(2)+1\nunset y\nx = 2\n\tx = 2\ny = 5\n\ty = 5\n\
+         x + y\n\t7\nclear\nx + y\n\tx+y\n"
+    );
+}
+
+/// The order of a derivative that does not fit in 32 bits is reported apart from one that is not a whole number.
+#[test]
+fn derivative_orders() {
+    let output = run(
+        "diff",
+        "diff(x^3, x, 3)\ndiff(x^3, x, 0)\ndiff(x^3, x, 99999999999)\n",
+        &[],
+    );
+    assert!(stdout(&output).starts_with("diff(x^3, x, 3)\n\t6\ndiff(x^3, x, 0)\n\tx^(3)\n"));
+    assert_eq!(
+        stderr(&output),
+        "\tError: the order of a derivative is too large, found 99999999999, the limit is 4294967295\n"
+    );
+}
+
+/// The factor command splits off the content and the rational roots, and keeps what has none as it is.
+#[test]
+fn factor_command() {
+    let output = run(
+        "factor",
+        "factor 2*x^3 - 2*x\nfactor x^2 - 2\nfactor x^3 + x + 1\n",
+        &[],
+    );
+    assert_eq!(
+        stdout(&output),
+        "factor 2*x^3 - 2*x\n\t2x(x-1)(x+1)\nfactor x^2 - 2\n\tx^(2)-2\nfactor x^3 + x + 1\n\tx^(3)+x+1\n"
+    );
+}
+
+#[test]
+fn interpolate_command() {
+    let output = run("interpolate", "interpolate((0,1), (1,2), (2,5))\n", &[]);
+    assert_eq!(
+        stdout(&output),
+        "interpolate((0,1), (1,2), (2,5))\n\tx^(2)+1\n"
+    );
+}
+
+#[test]
+fn mod_command() {
+    let output = run(
+        "mod",
+        "mod((x+1)^5, 5)\nmod(x^2 + 1/2, 5)\nmod(x, 6)\n",
+        &["--keep-going"],
+    );
+    assert!(
+        stdout(&output).starts_with("mod((x+1)^5, 5)\n\tx^(5)+1\nmod(x^2 + 1/2, 5)\n\tx^(2)+3\n")
+    );
+    assert_eq!(
+        stderr(&output),
+        "\tError: 6 is not a prime number, arithmetic modulo it is not supported\n"
+    );
+}
+
+/// Equations without a degree once simplified hold everywhere or nowhere.
+#[test]
+fn solve_without_degree() {
+    let output = run(
+        "solve_zero",
+        "solve 0 = 0\nsolve x - x = 1\n0 / (x + 1)\n",
+        &[],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "solve 0 = 0\n\tinfinitely many solutions\nsolve x - x = 1\n\tno solution\n0 / (x + 1)\n\t0\n"
+    );
+}
+
+/// The same ratio prints the same way whichever way it was built, with a positive leading coefficient below.
+#[test]
+fn ratio_signs_are_normalized() {
+    let inputs = [
+        "(x+1)/(x-2)",
+        "(-x-1)/(-x+2)",
+        "((x+1)*(x-1))/((x-2)*(x-1))",
+        "-(x+1)/(2-x)",
+    ];
+    let output = run("ratio_signs", &(inputs.join("\n") + "\n"), &[]);
+    let printed = stdout(&output);
+    let results: Vec<&str> = printed.lines().skip(1).step_by(2).collect();
+    assert_eq!(results, ["\t(x+1) / (x-2)"; 4]);
+
+    let output = run("constant_signs", "(x+1)/(-2)\n(-x-1)/2\n", &[]);
+    assert_eq!(
+        stdout(&output),
+        "(x+1)/(-2)\n\t(-x-1) / (2)\n(-x-1)/2\n\t(-x-1) / (2)\n"
+    );
+}
@@ -0,0 +1,45 @@
+//! Runs the interpreter on the same statements written with different line endings and whitespace, which must all give
+//! the output of the file with plain `\n` line endings.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Runs the interpreter on a fixture, copied to `input.txt` in a directory of its own, and returns what it prints.
+fn run(fixture: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("sym_tfg_{}_{}", std::process::id(), fixture));
+    fs::create_dir_all(&dir).unwrap();
+    let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(fixture);
+    fs::copy(source, dir.join("input.txt")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_sym_tfg"))
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(output.status.success(), "{} failed to run", fixture);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn crlf_line_endings() {
+    assert_eq!(run("crlf.txt"), run("lf.txt"));
+}
+
+#[test]
+fn no_final_newline() {
+    let expected = run("lf.txt");
+    // The last statement is the one a missing newline could lose
+    assert!(expected.ends_with("solve x^2 - 4\n\tx\t= 2\n\tx\t= -2\n"));
+    assert_eq!(run("no_final_newline.txt"), expected);
+}
+
+/// The statements are printed as written, so the tabs show up in the output where the spaces were.
+#[test]
+fn tabs_as_whitespace() {
+    assert_eq!(
+        run("tabs.txt").replace('\t', " "),
+        run("lf.txt").replace('\t', " ")
+    );
+}